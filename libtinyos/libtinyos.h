@@ -20,6 +20,8 @@ void __c_yield(void);
 
 void __print(const char *buf);
 
+int atexit(void (*func)(void));
+
 void free(uint8_t *ptr);
 
 uint8_t *malloc(size_t size);
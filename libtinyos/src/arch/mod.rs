@@ -1 +1 @@
-mod x86_64;
+pub(crate) mod x86_64;
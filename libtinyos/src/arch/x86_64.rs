@@ -1 +1,9 @@
-// TODO
+use core::arch::asm;
+
+/// returns the frame pointer (rbp) of the calling function
+#[inline(always)]
+pub(crate) fn frame_pointer() -> *const usize {
+    let fp: *const usize;
+    unsafe { asm!("mov {}, rbp", out(reg) fp, options(nomem, nostack, preserves_flags)) };
+    fp
+}
@@ -1,10 +1,13 @@
 use core::{
     alloc::{GlobalAlloc, Layout},
-    ffi::{CStr, c_char},
+    ffi::{CStr, c_char, c_int},
     mem,
 };
 
-use crate::syscalls::{self, STDOUT_FILENO};
+use crate::{
+    process,
+    syscalls::{self, STDOUT_FILENO},
+};
 
 #[cfg(feature = "alloc")]
 use crate::tiny_alloc;
@@ -30,7 +33,18 @@ pub unsafe extern "C" fn __c_write(handle: u32, buf: *const u8, len: usize) -> i
 
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn __c_exit(status: i64) -> ! {
-    unsafe { syscalls::exit(status) }
+    process::exit(status)
+}
+
+/// registers func to be called on exit. returns 0 on success.
+// not exported under the mock kernel, where it would clash with the hosts libc
+#[cfg_attr(not(feature = "mock-kernel"), unsafe(no_mangle))]
+pub extern "C" fn atexit(func: extern "C" fn()) -> c_int {
+    if process::at_exit_c(func).is_ok() {
+        0
+    } else {
+        -1
+    }
 }
 
 #[unsafe(no_mangle)]
@@ -44,13 +58,14 @@ pub extern "C" fn __c_yield() {
     unsafe { syscalls::yield_now() };
 }
 
-#[cfg(feature = "alloc")]
+#[cfg(all(feature = "alloc", not(feature = "mock-kernel")))]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn malloc(size: usize) -> *mut u8 {
     let layout = Layout::from_size_align(size, mem::align_of::<usize>()).unwrap();
     unsafe { tiny_alloc::GLOBAL_ALLOC.alloc(layout) }
 }
 
+#[cfg(not(feature = "mock-kernel"))]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn free(ptr: *mut u8) {
     // TODO need to keep a map of ptr -> layout to deallocate the correct chunk
@@ -19,7 +19,8 @@ const MAX_HEAP_SIZE: usize = (MAX_HEAP_ADDR - HEAP_START_ADDR) as usize;
 
 const ALIGN: usize = 4096; // as kernel pages are 4 KiB currently
 
-#[global_allocator]
+// with the mock kernel, the hosts allocator is used instead
+#[cfg_attr(not(feature = "mock-kernel"), global_allocator)]
 pub(crate) static GLOBAL_ALLOC: EnsureInitAlloc = EnsureInitAlloc::empty();
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -28,6 +29,18 @@ pub struct AllocData {
     used: usize,
 }
 
+impl AllocData {
+    /// free bytes on the heap
+    pub fn free(&self) -> usize {
+        self.free
+    }
+
+    /// used bytes on the heap
+    pub fn used(&self) -> usize {
+        self.used
+    }
+}
+
 pub fn alloc_data() -> Option<AllocData> {
     let inner = GLOBAL_ALLOC.inner.get()?.lock();
     Some(AllocData {
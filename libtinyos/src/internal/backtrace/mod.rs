@@ -0,0 +1,208 @@
+//! Stack backtraces by walking the frame pointer chain.
+//!
+//! Programs need to be built with frame pointers (see `target.json` and `.cargo/config.toml`), otherwise the chain is
+//! broken and only few or bogus frames are found.
+//! Return addresses are symbolized with a table of function symbols embedded into the `.tinyos_symbols` section of
+//! the binary (reserved by `user.ld` and filled in by `scripts/embed-symbols.sh` after linking).
+//! Without the table, only raw addresses are printed.
+
+use core::{
+    fmt::{self, Write},
+    hint, ptr,
+};
+
+use crate::{arch::x86_64::frame_pointer, io::FdWriter, syscalls::FileDescriptor};
+
+/// the maximum number of frames walked
+pub const MAX_FRAMES: usize = 64;
+
+/// size of the embedded symbol table, must match the section filled in by `scripts/embed-symbols.sh`
+pub const SYMBOL_TABLE_SIZE: usize = 64 * 1024;
+
+/// The embedded symbol table, in the format of `nm -n`: one `<hex address> <type> <name>` line per symbol,
+/// terminated by a nul byte.
+/// Zeroed unless filled in after linking.
+#[used]
+#[unsafe(link_section = ".tinyos_symbols")]
+static SYMBOL_TABLE: [u8; SYMBOL_TABLE_SIZE] = [0; SYMBOL_TABLE_SIZE];
+
+/// An iterator over the return addresses on the stack, starting with the caller of the function which created it.
+pub struct Frames {
+    fp: *const usize,
+    depth: usize,
+}
+
+impl Frames {
+    /// starts walking the stack at the current frame
+    #[inline(always)]
+    pub fn capture() -> Self {
+        Self {
+            fp: frame_pointer(),
+            depth: 0,
+        }
+    }
+
+    /// starts walking the stack at the frame pointer fp
+    ///
+    /// # Safety
+    /// fp must be null or point to a valid frame record (saved frame pointer followed by the return address),
+    /// and so must every frame record linked from it
+    pub unsafe fn from_frame_pointer(fp: *const usize) -> Self {
+        Self { fp, depth: 0 }
+    }
+}
+
+impl Iterator for Frames {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fp.is_null() || !self.fp.is_aligned() || self.depth >= MAX_FRAMES {
+            return None;
+        }
+        let (next, ret) = unsafe { (*self.fp as *const usize, *self.fp.add(1)) };
+        if ret == 0 {
+            return None;
+        }
+        // the stack grows down, so a callers frame is always above its callees frame
+        self.fp = if next > self.fp { next } else { ptr::null() };
+        self.depth += 1;
+        Some(ret)
+    }
+}
+
+/// The function containing an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol<'a> {
+    pub name: &'a str,
+    pub addr: usize,
+}
+
+/// looks up the function containing addr in the embedded symbol table
+pub fn resolve(addr: usize) -> Option<Symbol<'static>> {
+    // the table is patched after linking, so the compiler may not assume it is zeroed
+    let table: &'static [u8; SYMBOL_TABLE_SIZE] = hint::black_box(&SYMBOL_TABLE);
+    resolve_in(table, addr)
+}
+
+fn resolve_in(table: &[u8], addr: usize) -> Option<Symbol<'_>> {
+    let len = table.iter().position(|&b| b == 0).unwrap_or(table.len());
+    let table = str::from_utf8(&table[..len]).ok()?;
+    table
+        .lines()
+        .filter_map(parse_line)
+        .filter(|sym| sym.addr <= addr)
+        .max_by_key(|sym| sym.addr)
+}
+
+fn parse_line(line: &str) -> Option<Symbol<'_>> {
+    let (addr, rest) = line.split_once(' ')?;
+    let (kind, name) = rest.split_once(' ')?;
+    if !matches!(kind, "T" | "t" | "W" | "w") {
+        return None;
+    }
+    Some(Symbol {
+        name: strip_hash(name),
+        addr: usize::from_str_radix(addr, 16).ok()?,
+    })
+}
+
+/// strips the hash of legacy mangled rust symbols, e.g. `core::panicking::panic::h0123456789abcdef`
+fn strip_hash(name: &str) -> &str {
+    match name.rsplit_once("::h") {
+        Some((path, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            path
+        }
+        _ => name,
+    }
+}
+
+/// writes a single frame of a backtrace
+pub fn write_frame(
+    w: &mut dyn Write,
+    index: usize,
+    ret: usize,
+    symbol: Option<Symbol<'_>>,
+) -> fmt::Result {
+    write!(w, "{:>4}: {:#018x}", index, ret)?;
+    if let Some(symbol) = symbol {
+        write!(w, " - {}+{:#x}", symbol.name, ret - symbol.addr)?;
+    }
+    w.write_char('\n')
+}
+
+/// Writes a backtrace of the calling thread to fd, without allocating.
+#[inline(always)]
+pub fn print(fd: FileDescriptor) {
+    print_frames(fd, Frames::capture());
+}
+
+/// Writes a backtrace of frames to fd, without allocating.
+pub fn print_frames(fd: FileDescriptor, frames: Frames) {
+    let mut w = FdWriter::<256>::new(fd);
+    _ = write_frames(&mut w, frames);
+    _ = w.flush();
+}
+
+/// Writes a symbolized backtrace of frames to w.
+pub fn write_frames(w: &mut dyn Write, frames: Frames) -> fmt::Result {
+    w.write_str("stack backtrace:\n")?;
+    for (i, ret) in frames.enumerate() {
+        // the return address points after the call, which may already belong to the next function
+        write_frame(w, i, ret, resolve(ret - 1))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::StackBuf;
+
+    const TABLE: &[u8] = b"0000000000401000 T _start\n\
+        0000000000401080 t core::fmt::write::h0123456789abcdef\n\
+        0000000000402000 R SOME_DATA\n\
+        0000000000402100 T main\n\0garbage";
+
+    #[test]
+    fn symbolize() {
+        assert_eq!(resolve_in(TABLE, 0x400fff), None);
+        assert_eq!(
+            resolve_in(TABLE, 0x401000),
+            Some(Symbol {
+                name: "_start",
+                addr: 0x401000
+            })
+        );
+        assert_eq!(
+            resolve_in(TABLE, 0x402050).unwrap().name,
+            "core::fmt::write"
+        );
+        assert_eq!(resolve_in(TABLE, 0x403000).unwrap().name, "main");
+        assert_eq!(resolve_in(&[0; 16], 0x401000), None);
+    }
+
+    #[test]
+    fn walk() {
+        // three frame records, the last one terminating the chain
+        let mut stack = [0usize; 6];
+        let base = stack.as_ptr() as usize;
+        stack[0] = base + 2 * size_of::<usize>();
+        stack[1] = 0x401010;
+        stack[2] = base + 4 * size_of::<usize>();
+        stack[3] = 0x402110;
+        stack[4] = 0;
+        stack[5] = 0x401004;
+        let frames = unsafe { Frames::from_frame_pointer(stack.as_ptr()) };
+        let mut rets = [0; 4];
+        let mut n = 0;
+        for ret in frames {
+            rets[n] = ret;
+            n += 1;
+        }
+        assert_eq!(&rets[..n], &[0x401010, 0x402110, 0x401004]);
+
+        let mut line = StackBuf::<64>::new();
+        write_frame(&mut line, 1, 0x402110, resolve_in(TABLE, 0x40210f)).unwrap();
+        assert_eq!(line.as_str(), "   1: 0x0000000000402110 - main+0x10\n");
+    }
+}
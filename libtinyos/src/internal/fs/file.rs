@@ -0,0 +1,346 @@
+use crate::{
+    io::{Error, ErrorKind, Read, Result, Seek, SeekFrom, Write, seek_offset},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, impl_owned_fd},
+    path::Path,
+    syscalls::{self, safe},
+};
+
+/// An open file. The descriptor is closed on drop.
+///
+/// The kernel cannot report the position or the length of a file, so both are tracked by the File.
+/// The length is known after opening with truncate, and is kept up to date by writes.
+/// Otherwise it is measured by reading to the end of the file once, when it is first needed,
+/// which requires read access.
+#[derive(Debug)]
+pub struct File {
+    fd: OwnedFd,
+    pos: Position,
+    len: Option<u64>,
+    append: bool,
+    readable: bool,
+}
+
+/// the position of the kernels cursor, as far as it is known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Known(u64),
+    /// at the end of the file, after a write in append mode while the length is unknown
+    End,
+    /// after converting from a raw descriptor, until the next absolute seek
+    Unknown,
+}
+
+impl File {
+    /// opens the file at path for reading
+    pub fn open<P: AsRef<Path> + ?Sized>(path: &P) -> Result<Self> {
+        OpenOptions::new().read(true).open(path)
+    }
+
+    /// opens the file at path for writing, creating it if it does not exist and truncating it if it does
+    pub fn create<P: AsRef<Path> + ?Sized>(path: &P) -> Result<Self> {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+    }
+
+    /// returns a new [`OpenOptions`], with every option unset
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    /// Creates a new File referring to the same file description, which shares the position in the kernel.
+    /// Both Files track the position and length on their own, so after using one,
+    /// the other should seek to an absolute position and may see an outdated length.
+    pub fn try_clone(&self) -> Result<Self> {
+        Ok(Self {
+            fd: self.fd.try_clone()?,
+            pos: self.pos,
+            len: self.len,
+            append: self.append,
+            readable: self.readable,
+        })
+    }
+
+    /// moves the kernels cursor to the absolute position pos
+    fn seek_to(&mut self, pos: u64) -> Result<u64> {
+        let offset = usize::try_from(pos)
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "seek position is too large"))?;
+        safe::seek(self.fd.as_raw_fd(), offset)?;
+        self.pos = Position::Known(pos);
+        Ok(pos)
+    }
+
+    /// returns the current position, measuring the file if the cursor is at its end
+    fn position(&mut self) -> Result<u64> {
+        match self.pos {
+            Position::Known(pos) => Ok(pos),
+            Position::End => self.len(),
+            Position::Unknown => Err(Error::new(
+                ErrorKind::Unsupported,
+                "the position of a file converted from a raw descriptor is unknown",
+            )),
+        }
+    }
+
+    /// returns the length of the file, measuring it if it is not known
+    fn len(&mut self) -> Result<u64> {
+        match self.len {
+            Some(len) => Ok(len),
+            None => self.measure(),
+        }
+    }
+
+    /// measures the length of the file by reading it from the start, which leaves the cursor at the end
+    fn measure(&mut self) -> Result<u64> {
+        if !self.readable {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "the length of a file opened without read access is unknown",
+            ));
+        }
+        let mut len = self.seek_to(0)?;
+        let mut buf = [0; MEASURE_CHUNK];
+        loop {
+            match safe::read(self.fd.as_raw_fd(), &mut buf, None)? {
+                0 => break,
+                n => len += n as u64,
+            }
+        }
+        self.pos = Position::Known(len);
+        self.len = Some(len);
+        Ok(len)
+    }
+}
+
+const MEASURE_CHUNK: usize = 512;
+
+impl_owned_fd!(File, pos: Position::Unknown, len: None, append: false, readable: true);
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = safe::read(self.fd.as_raw_fd(), buf, None)?;
+        self.pos = match self.pos {
+            Position::Known(pos) => {
+                let end = pos + n as u64;
+                if self.len.is_some_and(|len| len < end) {
+                    // another handle made the file longer
+                    self.len = None;
+                }
+                Position::Known(end)
+            }
+            Position::End if n == 0 => Position::End,
+            // the file grew since the last write
+            _ => Position::Unknown,
+        };
+        Ok(n)
+    }
+}
+
+impl Write for File {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = safe::write(self.fd.as_raw_fd(), buf)?;
+        let written = n as u64;
+        (self.pos, self.len) = match (self.pos, self.len) {
+            (_, Some(len)) if self.append => (Position::Known(len + written), Some(len + written)),
+            _ if self.append => (Position::End, None),
+            (Position::Known(pos), len) => (
+                Position::Known(pos + written),
+                len.map(|len| len.max(pos + written)),
+            ),
+            (pos, _) => (pos, None),
+        };
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::Current(offset) => seek_offset(self.position()?, offset)?,
+            SeekFrom::End(offset) => seek_offset(self.len()?, offset)?,
+        };
+        self.seek_to(pos)
+    }
+
+    /// returns the tracked length, only measuring the file if it is unknown
+    fn stream_len(&mut self) -> Result<u64> {
+        if let Some(len) = self.len {
+            return Ok(len);
+        }
+        let pos = self.position()?;
+        let len = self.measure()?;
+        self.seek_to(pos)?;
+        Ok(len)
+    }
+}
+
+/// Options to open a [`File`] with, a builder over the kernels [`OpenOptions`](syscalls::OpenOptions) flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenOptions {
+    flags: syscalls::OpenOptions,
+}
+
+impl OpenOptions {
+    pub const fn new() -> Self {
+        Self {
+            flags: syscalls::OpenOptions::empty(),
+        }
+    }
+
+    pub const fn read(self, read: bool) -> Self {
+        self.set(syscalls::OpenOptions::READ, read)
+    }
+
+    pub const fn write(self, write: bool) -> Self {
+        self.set(syscalls::OpenOptions::WRITE, write)
+    }
+
+    /// writes go to the end of the file. implies write
+    pub const fn append(self, append: bool) -> Self {
+        let options = self.set(syscalls::OpenOptions::APPEND, append);
+        if append { options.write(true) } else { options }
+    }
+
+    /// creates the file if it does not exist
+    pub const fn create(self, create: bool) -> Self {
+        self.set(syscalls::OpenOptions::CREATE, create)
+    }
+
+    /// truncates the file to length 0 if it exists
+    pub const fn truncate(self, truncate: bool) -> Self {
+        self.set(syscalls::OpenOptions::TRUNCATE, truncate)
+    }
+
+    /// returns the kernel flags these options open files with
+    pub const fn flags(&self) -> syscalls::OpenOptions {
+        self.flags
+    }
+
+    /// opens the file at path with these options
+    pub fn open<P: AsRef<Path> + ?Sized>(&self, path: &P) -> Result<File> {
+        let fd = safe::open(path, self.flags)?;
+        Ok(File {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+            pos: Position::Known(0),
+            len: self
+                .flags
+                .contains(syscalls::OpenOptions::TRUNCATE)
+                .then_some(0),
+            append: self.flags.contains(syscalls::OpenOptions::APPEND),
+            readable: self.flags.contains(syscalls::OpenOptions::READ),
+        })
+    }
+
+    const fn set(mut self, flag: syscalls::OpenOptions, value: bool) -> Self {
+        self.flags = if value {
+            self.flags.union(flag)
+        } else {
+            self.flags.difference(flag)
+        };
+        self
+    }
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<syscalls::OpenOptions> for OpenOptions {
+    fn from(flags: syscalls::OpenOptions) -> Self {
+        Self { flags }
+    }
+}
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+    use crate::{os::fd::IntoRawFd, syscalls::mock};
+
+    #[test]
+    fn open_write_read() {
+        File::create("/ram/fs_file")
+            .unwrap()
+            .write_all(b"hello")
+            .unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open("/ram/fs_file")
+            .unwrap();
+        file.write_all(b" world").unwrap();
+        assert_eq!(mock::read_file("/ram/fs_file").unwrap(), b"hello world");
+
+        let mut file = File::open(Path::new("/ram/fs_file")).unwrap();
+        let mut buf = [0; 5];
+        file.seek(SeekFrom::Start(6)).unwrap();
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"world");
+        assert!(file.write(b"x").is_err());
+
+        let err = File::open("/ram/fs_missing").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn seeking() {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open("/ram/fs_seek")
+            .unwrap();
+        file.write_all(b"0123456789").unwrap();
+        assert_eq!(file.stream_position().unwrap(), 10);
+        assert_eq!(file.seek(SeekFrom::Current(-4)).unwrap(), 6);
+        let mut buf = [0; 2];
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"67");
+        assert_eq!(file.stream_len().unwrap(), 10);
+        assert_eq!(file.stream_position().unwrap(), 8);
+        assert_eq!(file.seek(SeekFrom::End(-3)).unwrap(), 7);
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"78");
+        assert!(file.seek(SeekFrom::Current(-20)).is_err());
+
+        let mut log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open("/ram/fs_seek")
+            .unwrap();
+        log.write_all(b"ab").unwrap();
+        assert_eq!(log.stream_position().unwrap(), 12);
+        log.seek(SeekFrom::Start(0)).unwrap();
+        log.write_all(b"c").unwrap();
+        assert_eq!(log.stream_len().unwrap(), 13);
+
+        // the length of write-only files is tracked from truncation on, as it cannot be measured
+        let mut out = File::create("/ram/fs_seek_out").unwrap();
+        out.write_all(b"header").unwrap();
+        assert_eq!(out.seek(SeekFrom::End(-2)).unwrap(), 4);
+        out.write_all(b"ER!").unwrap();
+        assert_eq!(out.stream_len().unwrap(), 7);
+        assert_eq!(mock::read_file("/ram/fs_seek_out").unwrap(), b"headER!");
+        let mut out = OpenOptions::new()
+            .append(true)
+            .open("/ram/fs_seek_out")
+            .unwrap();
+        out.write_all(b"?").unwrap();
+        assert_eq!(out.stream_len().unwrap_err().kind(), ErrorKind::Unsupported);
+
+        let mut raw = unsafe { File::from_raw_fd(log.into_raw_fd()) };
+        assert_eq!(
+            raw.stream_position().unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+        assert_eq!(raw.seek(SeekFrom::End(0)).unwrap(), 13);
+    }
+}
@@ -1 +1,96 @@
-// TODO
+//! Files and the file system.
+//!
+//! The kernel has no calls to list directories or to query the metadata of files yet,
+//! so [`exists`] is the only way to inspect a path without opening it yourself.
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::{
+    io::{DEFAULT_BUF_SIZE, ErrorKind, Read},
+    path::PathBuf,
+};
+use crate::{
+    io::{Result, Write},
+    path::Path,
+};
+
+mod file;
+pub use file::{File, OpenOptions};
+
+/// reads the entire file at path
+#[cfg(feature = "alloc")]
+pub fn read<P: AsRef<Path> + ?Sized>(path: &P) -> Result<Vec<u8>> {
+    // read_to_end doubles the buffer whenever it is full, so large files need few reads
+    let mut buf = Vec::with_capacity(DEFAULT_BUF_SIZE);
+    File::open(path)?.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// reads the entire file at path, which must be valid utf8
+#[cfg(feature = "alloc")]
+pub fn read_to_string<P: AsRef<Path> + ?Sized>(path: &P) -> Result<String> {
+    Ok(String::from_utf8(read(path)?).map_err(|e| e.utf8_error())?)
+}
+
+/// writes contents to the file at path, creating it if it does not exist and replacing its contents if it does
+pub fn write<P: AsRef<Path> + ?Sized, C: AsRef<[u8]>>(path: &P, contents: C) -> Result<()> {
+    File::create(path)?.write_all(contents.as_ref())
+}
+
+/// appends contents to the file at path, creating it if it does not exist
+pub fn append<P: AsRef<Path> + ?Sized, C: AsRef<[u8]>>(path: &P, contents: C) -> Result<()> {
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)?
+        .write_all(contents.as_ref())
+}
+
+/// Returns whether a file or directory exists at path, i.e. whether opening it fails with anything but not found.
+#[cfg(feature = "alloc")]
+pub fn exists<P: AsRef<Path> + ?Sized>(path: &P) -> bool {
+    let mut path = PathBuf::from(path.as_ref());
+    path.canonicalize();
+    match File::open(&path) {
+        Ok(_) => true,
+        Err(e) => e.kind() != ErrorKind::NotFound,
+    }
+}
+
+#[cfg(all(test, feature = "mock-kernel", feature = "alloc"))]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::syscalls::mock;
+
+    #[test]
+    fn whole_files() {
+        let data = vec![7; 3 * DEFAULT_BUF_SIZE + 5];
+        write("/ram/fs_whole", &data).unwrap();
+        assert_eq!(read("/ram/fs_whole").unwrap(), data);
+
+        write("/ram/fs_whole", "first\n").unwrap();
+        append("/ram/fs_whole", "second\n").unwrap();
+        append("/ram/fs_appended", b"new").unwrap();
+        assert_eq!(read_to_string("/ram/fs_whole").unwrap(), "first\nsecond\n");
+        assert_eq!(read_to_string("/ram/fs_appended").unwrap(), "new");
+
+        write("/ram/fs_whole", [0xff, 0xfe]).unwrap();
+        assert_eq!(
+            read_to_string("/ram/fs_whole").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn existence() {
+        mock::write_file("/ram/fs_exists/a.txt", b"hello");
+        assert!(exists("/ram/fs_exists/a.txt"));
+        assert!(exists("/ram/fs_exists/../fs_exists/a.txt"));
+        assert!(exists("/ram/fs_exists"));
+        assert!(!exists("/ram/fs_exists/missing"));
+    }
+}
@@ -0,0 +1,472 @@
+use core::{fmt, mem::ManuallyDrop, ptr};
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+use super::{Error, ErrorKind, Read, Result, Write};
+
+/// the default buffer size of [`BufReader`], [`BufWriter`] and [`LineWriter`]
+pub const DEFAULT_BUF_SIZE: usize = 1024;
+
+/// A reader with an internal buffer, which allows reading lines and other delimited data.
+pub trait BufRead: Read {
+    /// returns the buffered data, filling the buffer from the underlying reader if it is empty.
+    /// an empty slice signals EOF.
+    fn fill_buf(&mut self) -> Result<&[u8]>;
+
+    /// marks amt bytes of the buffer as read, so they are not returned by fill_buf again
+    fn consume(&mut self, amt: usize);
+
+    /// reads until and including byte, or until EOF, and appends the data to buf.
+    /// returns the number of bytes read.
+    #[cfg(feature = "alloc")]
+    fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> Result<usize> {
+        let mut read = 0;
+        loop {
+            let (done, used) = {
+                let available = match self.fill_buf() {
+                    Ok(available) => available,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                match available.iter().position(|&b| b == byte) {
+                    Some(i) => {
+                        buf.extend_from_slice(&available[..=i]);
+                        (true, i + 1)
+                    }
+                    None => {
+                        buf.extend_from_slice(available);
+                        (available.is_empty(), available.len())
+                    }
+                }
+            };
+            self.consume(used);
+            read += used;
+            if done {
+                return Ok(read);
+            }
+        }
+    }
+
+    /// reads a line including its trailing newline, if any, and appends it to buf.
+    /// returns the number of bytes read, which is 0 at EOF.
+    #[cfg(feature = "alloc")]
+    fn read_line(&mut self, buf: &mut String) -> Result<usize> {
+        let mut bytes = Vec::new();
+        let n = self.read_until(b'\n', &mut bytes)?;
+        buf.push_str(str::from_utf8(&bytes)?);
+        Ok(n)
+    }
+
+    /// returns an iterator over the lines of this reader, without their line endings
+    #[cfg(feature = "alloc")]
+    fn lines(self) -> Lines<Self>
+    where
+        Self: Sized,
+    {
+        Lines { reader: self }
+    }
+
+    /// returns an iterator over the parts of this reader separated by byte, without the separator
+    #[cfg(feature = "alloc")]
+    fn split(self, byte: u8) -> Split<Self>
+    where
+        Self: Sized,
+    {
+        Split { reader: self, byte }
+    }
+}
+
+impl<B: BufRead + ?Sized> BufRead for &mut B {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        (**self).fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        (**self).consume(amt)
+    }
+}
+
+impl BufRead for &[u8] {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(self)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        *self = &self[amt..];
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`], see [`BufRead::lines`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct Lines<B> {
+    reader: B,
+}
+
+#[cfg(feature = "alloc")]
+impl<B: BufRead> Iterator for Lines<B> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some(Ok(line))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// An iterator over the parts of a [`BufRead`] separated by a byte, see [`BufRead::split`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct Split<B> {
+    reader: B,
+    byte: u8,
+}
+
+#[cfg(feature = "alloc")]
+impl<B: BufRead> Iterator for Split<B> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut part = Vec::new();
+        match self.reader.read_until(self.byte, &mut part) {
+            Ok(0) => None,
+            Ok(_) => {
+                if part.last() == Some(&self.byte) {
+                    part.pop();
+                }
+                Some(Ok(part))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Adds an inline buffer of N bytes to a reader, so that small reads do not each cause a syscall.
+pub struct BufReader<R, const N: usize = DEFAULT_BUF_SIZE> {
+    inner: R,
+    buf: [u8; N],
+    pos: usize,
+    filled: usize,
+}
+
+impl<R, const N: usize> BufReader<R, N> {
+    pub const fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: [0; N],
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// returns the underlying reader. reading from it directly skips the buffered data.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// returns the underlying reader. any buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// returns the buffered data, without filling the buffer
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    fn discard_buffer(&mut self) {
+        self.pos = 0;
+        self.filled = 0;
+    }
+}
+
+impl<R: Read, const N: usize> Read for BufReader<R, N> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        // large reads bypass the buffer, instead of copying through it
+        if self.pos == self.filled && buf.len() >= N {
+            self.discard_buffer();
+            return self.inner.read(buf);
+        }
+        let mut available = self.fill_buf()?;
+        let n = available.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read, const N: usize> BufRead for BufReader<R, N> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos >= self.filled {
+            self.filled = self.inner.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        Ok(self.buffer())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.filled);
+    }
+}
+
+impl<R: fmt::Debug, const N: usize> fmt::Debug for BufReader<R, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufReader")
+            .field("inner", &self.inner)
+            .field("buffered", &(self.filled - self.pos))
+            .field("capacity", &N)
+            .finish()
+    }
+}
+
+/// Adds an inline buffer of N bytes to a writer, so that small writes do not each cause a syscall.
+/// The buffer is written out when it is full, on [`Write::flush`] and on drop (ignoring any errors).
+pub struct BufWriter<W: Write, const N: usize = DEFAULT_BUF_SIZE> {
+    inner: W,
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<W: Write, const N: usize> BufWriter<W, N> {
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// returns the underlying writer. writing to it directly skips the buffered data.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// writes out the buffer and returns the underlying writer
+    pub fn into_inner(mut self) -> Result<W> {
+        self.flush_buf()?;
+        let this = ManuallyDrop::new(self);
+        // SAFETY: this is never used again, so inner is not dropped twice
+        Ok(unsafe { ptr::read(&this.inner) })
+    }
+
+    /// returns the buffered data
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// writes the buffered data to the underlying writer, without flushing it.
+    /// on error, the data which was not written stays buffered.
+    fn flush_buf(&mut self) -> Result<()> {
+        let mut written = 0;
+        let res = loop {
+            if written == self.len {
+                break Ok(());
+            }
+            match self.inner.write(&self.buf[written..self.len]) {
+                Ok(0) => {
+                    break Err(Error::new(
+                        ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    ));
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        self.buf.copy_within(written..self.len, 0);
+        self.len -= written;
+        res
+    }
+}
+
+impl<W: Write, const N: usize> Write for BufWriter<W, N> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.len + buf.len() > N {
+            self.flush_buf()?;
+        }
+        // large writes bypass the buffer, instead of copying through it
+        if buf.len() >= N {
+            return self.inner.write(buf);
+        }
+        self.buf[self.len..self.len + buf.len()].copy_from_slice(buf);
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write, const N: usize> Drop for BufWriter<W, N> {
+    fn drop(&mut self) {
+        _ = self.flush_buf();
+    }
+}
+
+impl<W: Write + fmt::Debug, const N: usize> fmt::Debug for BufWriter<W, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufWriter")
+            .field("inner", &self.inner)
+            .field("buffered", &self.len)
+            .field("capacity", &N)
+            .finish()
+    }
+}
+
+/// Like [`BufWriter`], but also writes out the buffer whenever a newline is written.
+pub struct LineWriter<W: Write, const N: usize = DEFAULT_BUF_SIZE> {
+    inner: BufWriter<W, N>,
+}
+
+impl<W: Write, const N: usize> LineWriter<W, N> {
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner: BufWriter::new(inner),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// returns the underlying writer. writing to it directly skips the buffered data.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    /// writes out the buffer and returns the underlying writer
+    pub fn into_inner(self) -> Result<W> {
+        self.inner.into_inner()
+    }
+
+    /// returns the buffered data, which never contains a complete line
+    pub fn buffer(&self) -> &[u8] {
+        self.inner.buffer()
+    }
+}
+
+impl<W: Write, const N: usize> Write for LineWriter<W, N> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let Some(i) = buf.iter().rposition(|&b| b == b'\n') else {
+            return self.inner.write(buf);
+        };
+        // only the complete lines are taken, the rest is written by the next call
+        let n = self.inner.write(&buf[..=i])?;
+        if n == i + 1 {
+            // the lines were accepted, so a failed flush is reported by a later write or flush
+            _ = self.inner.flush_buf();
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write + fmt::Debug, const N: usize> fmt::Debug for LineWriter<W, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineWriter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    /// records the size of every write
+    #[derive(Default)]
+    struct Recorder {
+        data: Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.data.extend_from_slice(buf);
+            self.writes.push(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reader_lines() {
+        // a small buffer, so lines span several fills
+        let reader = BufReader::<_, 4>::new(&b"first line\r\nsecond\n\nlast"[..]);
+        let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, ["first line", "second", "", "last"]);
+
+        let parts: Vec<Vec<u8>> = BufReader::<_, 3>::new(&b"a,bc,,def"[..])
+            .split(b',')
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(parts, [&b"a"[..], b"bc", b"", b"def"]);
+
+        let mut reader = BufReader::<_, 4>::new(&b"ab\ncd"[..]);
+        let mut line = "x".to_string();
+        assert_eq!(reader.read_line(&mut line).unwrap(), 3);
+        assert_eq!(line, "xab\n");
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"cd");
+    }
+
+    #[test]
+    fn writers() {
+        let mut w = BufWriter::<_, 8>::new(Recorder::default());
+        w.write_all(b"abc").unwrap();
+        w.write_all(b"defg").unwrap();
+        assert!(w.get_ref().writes.is_empty());
+        w.write_all(b"hi").unwrap();
+        w.write_all(b"0123456789").unwrap();
+        let r = w.into_inner().unwrap();
+        assert_eq!(r.data, b"abcdefghi0123456789");
+        assert_eq!(r.writes, [7, 2, 10]);
+
+        let mut w = LineWriter::<_, 16>::new(Recorder::default());
+        w.write_all(b"one\ntw").unwrap();
+        assert_eq!(w.get_ref().data, b"one\n");
+        w.write_all(b"o\nthree").unwrap();
+        assert_eq!(w.get_ref().data, b"one\ntwo\n");
+        assert_eq!(w.buffer(), b"three");
+        drop(w);
+    }
+}
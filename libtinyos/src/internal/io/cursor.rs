@@ -0,0 +1,180 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use super::{BufRead, Error, ErrorKind, Read, Result, Seek, SeekFrom, Write, traits::seek_offset};
+
+/// An in-memory buffer with a position, implementing [`Read`], [`Write`] and [`Seek`].
+/// Writes overwrite the data at the position, growing a `Vec` if needed, but never a slice.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cursor<T> {
+    inner: T,
+    pos: u64,
+}
+
+impl<T> Cursor<T> {
+    pub const fn new(inner: T) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub const fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    /// returns the data after the position
+    pub fn remaining_slice(&self) -> &[u8] {
+        let data = self.inner.as_ref();
+        let start = self.pos.min(data.len() as u64) as usize;
+        &data[start..]
+    }
+}
+
+impl<T: AsRef<[u8]>> Read for Cursor<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.remaining_slice().read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: AsRef<[u8]>> BufRead for Cursor<T> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(self.remaining_slice())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.pos = match pos {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::End(offset) => seek_offset(self.inner.as_ref().len() as u64, offset)?,
+            SeekFrom::Current(offset) => seek_offset(self.pos, offset)?,
+        };
+        Ok(self.pos)
+    }
+}
+
+/// writes as much of buf as fits into the slice at pos
+fn slice_write(pos: &mut u64, slice: &mut [u8], buf: &[u8]) -> Result<usize> {
+    let start = (*pos).min(slice.len() as u64) as usize;
+    let n = (&mut slice[start..]).write(buf)?;
+    *pos += n as u64;
+    Ok(n)
+}
+
+/// writes buf into the vec at pos, filling any gap with zeroes
+#[cfg(feature = "alloc")]
+fn vec_write(pos: &mut u64, vec: &mut Vec<u8>, buf: &[u8]) -> Result<usize> {
+    let start = usize::try_from(*pos).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "cursor position exceeds maximum possible vector length",
+        )
+    })?;
+    let end = start + buf.len();
+    if vec.len() < end {
+        vec.resize(end, 0);
+    }
+    vec[start..end].copy_from_slice(buf);
+    *pos = end as u64;
+    Ok(buf.len())
+}
+
+impl Write for Cursor<&mut [u8]> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        slice_write(&mut self.pos, self.inner, buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> Write for Cursor<[u8; N]> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        slice_write(&mut self.pos, &mut self.inner, buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Write for Cursor<Vec<u8>> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        vec_write(&mut self.pos, &mut self.inner, buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Write for Cursor<&mut Vec<u8>> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        vec_write(&mut self.pos, self.inner, buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_write_seek() {
+        let mut c = Cursor::new([0u8; 8]);
+        c.write_all(b"abcdef").unwrap();
+        assert_eq!(c.seek(SeekFrom::End(-4)).unwrap(), 4);
+        c.write_all(b"XY").unwrap();
+        assert!(c.write_all(b"too long").is_err());
+        c.rewind().unwrap();
+        let mut buf = [0; 6];
+        c.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"abcdXY");
+        assert_eq!(c.stream_position().unwrap(), 6);
+        assert!(c.seek(SeekFrom::Current(-7)).is_err());
+
+        let mut c = Cursor::new(&b"line\nrest"[..]);
+        let mut line = [0; 5];
+        c.read_exact(&mut line).unwrap();
+        assert_eq!(c.fill_buf().unwrap(), b"rest");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn vec_grows() {
+        let mut c = Cursor::new(Vec::new());
+        c.seek(SeekFrom::Start(2)).unwrap();
+        c.write_all(b"ab").unwrap();
+        c.set_position(0);
+        c.write_all(b"x").unwrap();
+        assert_eq!(c.into_inner(), b"x\0ab");
+    }
+}
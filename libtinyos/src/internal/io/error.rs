@@ -0,0 +1,177 @@
+use core::{fmt::Display, str::Utf8Error};
+
+use tinyos_abi::types::SysErrCode;
+
+use crate::{os::EnvErr, process::ProcessError};
+
+#[cfg(feature = "alloc")]
+use crate::thread::ThreadingErr;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// A general category of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    WouldBlock,
+    TimedOut,
+    InvalidInput,
+    InvalidData,
+    OutOfMemory,
+    BrokenPipe,
+    UnexpectedEof,
+    WriteZero,
+    Interrupted,
+    Unsupported,
+    Other,
+}
+
+impl ErrorKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotFound => "entity not found",
+            Self::PermissionDenied => "permission denied",
+            Self::AlreadyExists => "entity already exists",
+            Self::WouldBlock => "operation would block",
+            Self::TimedOut => "timed out",
+            Self::InvalidInput => "invalid input parameter",
+            Self::InvalidData => "invalid data",
+            Self::OutOfMemory => "out of memory",
+            Self::BrokenPipe => "broken pipe",
+            Self::UnexpectedEof => "unexpected end of file",
+            Self::WriteZero => "write zero",
+            Self::Interrupted => "operation interrupted",
+            Self::Unsupported => "unsupported",
+            Self::Other => "other error",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<SysErrCode> for ErrorKind {
+    #[allow(unreachable_patterns)]
+    fn from(value: SysErrCode) -> Self {
+        match value {
+            SysErrCode::NotFound => Self::NotFound,
+            SysErrCode::PermissionDenied => Self::PermissionDenied,
+            SysErrCode::WouldBlock => Self::WouldBlock,
+            SysErrCode::TimedOut => Self::TimedOut,
+            SysErrCode::InvalidArgument => Self::InvalidInput,
+            SysErrCode::OutOfMemory => Self::OutOfMemory,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// The error type for all fallible operations in libtinyos.
+///
+/// An Error either wraps the [`SysErrCode`] returned by the kernel,
+/// or is created in user space from an [`ErrorKind`] and an optional static message.
+/// It never allocates, so it can be used without the alloc feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Error {
+    repr: Repr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repr {
+    Sys(SysErrCode),
+    Simple(ErrorKind),
+    Message(ErrorKind, &'static str),
+}
+
+impl Error {
+    pub const fn new(kind: ErrorKind, msg: &'static str) -> Self {
+        Self {
+            repr: Repr::Message(kind, msg),
+        }
+    }
+
+    pub const fn from_sys(code: SysErrCode) -> Self {
+        Self {
+            repr: Repr::Sys(code),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self.repr {
+            Repr::Sys(code) => code.into(),
+            Repr::Simple(kind) | Repr::Message(kind, _) => kind,
+        }
+    }
+
+    /// returns the kernel error code, if this error originated from a syscall
+    pub fn sys_code(&self) -> Option<SysErrCode> {
+        match self.repr {
+            Repr::Sys(code) => Some(code),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.repr {
+            Repr::Sys(code) => write!(f, "{} (os error {:?})", self.kind(), code),
+            Repr::Simple(kind) => write!(f, "{}", kind),
+            Repr::Message(_, msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl From<ErrorKind> for Error {
+    fn from(value: ErrorKind) -> Self {
+        Self {
+            repr: Repr::Simple(value),
+        }
+    }
+}
+
+impl From<SysErrCode> for Error {
+    fn from(value: SysErrCode) -> Self {
+        Self::from_sys(value)
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(_value: Utf8Error) -> Self {
+        Self::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+    }
+}
+
+impl From<EnvErr> for Error {
+    fn from(value: EnvErr) -> Self {
+        match value {
+            EnvErr::Utf8(e) => e.into(),
+            EnvErr::InvalidPtr => Self::new(ErrorKind::InvalidInput, "invalid environment pointer"),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<ThreadingErr> for Error {
+    fn from(value: ThreadingErr) -> Self {
+        match value {
+            ThreadingErr::Fail => Self::new(ErrorKind::Other, "thread operation failed"),
+        }
+    }
+}
+
+impl From<ProcessError> for Error {
+    fn from(value: ProcessError) -> Self {
+        match value {
+            ProcessError::Sys(code) => code.into(),
+            ProcessError::Io(e) => e,
+        }
+    }
+}
@@ -1,4 +1,30 @@
-use crate::syscalls::{self, FileDescriptor, SysResult};
+use crate::syscalls::{FileDescriptor, SysResult, safe};
+
+mod buffered;
+mod cursor;
+mod error;
+mod pipe;
+mod poll;
+mod print;
+#[cfg(feature = "alloc")]
+pub mod readline;
+mod stdio;
+mod traits;
+mod util;
+pub use buffered::{BufRead, BufReader, BufWriter, DEFAULT_BUF_SIZE, LineWriter};
+#[cfg(feature = "alloc")]
+pub use buffered::{Lines, Split};
+pub use cursor::Cursor;
+pub use error::{Error, ErrorKind, Result};
+pub use pipe::{PipeReader, PipeWriter, pipe, pipe_with_capacity};
+pub use poll::{POLL_BUF_SIZE, PollFd, PollFlags, poll};
+#[doc(hidden)]
+pub use print::{_eprint, _print, _serial_print};
+pub use print::{FdWriter, PRINT_BUF_SIZE};
+pub use stdio::{Stderr, StderrLock, Stdin, Stdout, StdoutLock, stderr, stdin, stdout};
+pub(crate) use traits::seek_offset;
+pub use traits::{Read, Seek, SeekFrom, Write};
+pub use util::{Chain, Empty, Sink, Take, Tee, copy, empty, sink, tee};
 
 #[macro_export]
 macro_rules! println {
@@ -10,22 +36,14 @@ macro_rules! println {
     };
 }
 
-#[cfg(feature = "alloc")]
 #[macro_export]
 macro_rules! print {
     () => {};
     ($($arg:tt)*) => {
-        $crate::io::print_str($crate::syscalls::STDOUT_FILENO, &alloc::format!($($arg)*)).unwrap()
+        $crate::io::_print(format_args!($($arg)*))
     };
 }
 
-#[cfg(not(feature = "alloc"))]
-#[macro_export]
-macro_rules! print {
-    () => {};
-    ($($arg:tt)*) => {};
-}
-
 #[macro_export]
 macro_rules! eprintln {
     () => {
@@ -36,22 +54,14 @@ macro_rules! eprintln {
     };
 }
 
-#[cfg(feature = "alloc")]
 #[macro_export]
 macro_rules! eprint {
     () => {};
     ($($arg:tt)*) => {
-        $crate::io::print_str($crate::syscalls::STDERR_FILENO, &alloc::format!($($arg)*)).unwrap()
+        $crate::io::_eprint(format_args!($($arg)*))
     };
 }
 
-#[cfg(not(feature = "alloc"))]
-#[macro_export]
-macro_rules! eprint {
-    () => {};
-    ($($arg:tt)*) => {};
-}
-
 #[macro_export]
 macro_rules! serial_println {
     () => {
@@ -62,30 +72,18 @@ macro_rules! serial_println {
     };
 }
 
-#[cfg(feature = "alloc")]
 #[macro_export]
 macro_rules! serial_print {
     () => {};
     ($($arg:tt)*) => {
-        $crate::io::dbg_print_str(&alloc::format!("\x1b[96m[USRINFO]\x1b[0m {}", format_args!($($arg)*))).unwrap()
+        $crate::io::_serial_print(format_args!($($arg)*))
     };
 }
 
-#[cfg(not(feature = "alloc"))]
-#[macro_export]
-macro_rules! serial_print {
-    () => {};
-    ($($arg:tt)*) => {};
-}
-
 pub fn dbg_print_str(s: &str) -> SysResult<()> {
-    let ptr = s.as_ptr();
-    let len = s.len();
-    unsafe { syscalls::dbg(ptr, len) }
+    safe::dbg(s)
 }
 
-pub fn print_str(fd: FileDescriptor, s: &str) -> SysResult<isize> {
-    let ptr = s.as_ptr();
-    let len = s.len();
-    unsafe { syscalls::write(fd, ptr, len) }
+pub fn print_str(fd: FileDescriptor, s: &str) -> SysResult<usize> {
+    safe::write(fd, s.as_bytes())
 }
@@ -0,0 +1,78 @@
+use super::{Read, Result, Write};
+use crate::{
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, impl_owned_fd},
+    syscalls::safe,
+};
+
+/// The read end of a pipe, see [`pipe`]. Reads return EOF once every writer is closed.
+/// The descriptor is closed on drop.
+#[derive(Debug)]
+pub struct PipeReader {
+    fd: OwnedFd,
+}
+
+/// The write end of a pipe, see [`pipe`]. Writes block while the pipe is full.
+/// The descriptor is closed on drop, which signals EOF to the reader once no other writer is left.
+#[derive(Debug)]
+pub struct PipeWriter {
+    fd: OwnedFd,
+}
+
+/// creates a new pipe with a capacity chosen by the kernel and returns its ends
+pub fn pipe() -> Result<(PipeReader, PipeWriter)> {
+    pipe_inner(None)
+}
+
+/// creates a new pipe, which buffers up to cap bytes, and returns its ends
+pub fn pipe_with_capacity(cap: usize) -> Result<(PipeReader, PipeWriter)> {
+    pipe_inner(Some(cap))
+}
+
+fn pipe_inner(cap: Option<usize>) -> Result<(PipeReader, PipeWriter)> {
+    let [reader, writer] = safe::pipe(cap)?;
+    unsafe {
+        Ok((
+            PipeReader::from_raw_fd(reader),
+            PipeWriter::from_raw_fd(writer),
+        ))
+    }
+}
+
+impl_owned_fd!(PipeReader);
+impl_owned_fd!(PipeWriter);
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Ok(safe::read(self.fd.as_raw_fd(), buf, None)?)
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        Ok(safe::write(self.fd.as_raw_fd(), buf)?)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "mock-kernel", feature = "alloc"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn eof_on_drop() {
+        let (mut reader, mut writer) = pipe_with_capacity(4).unwrap();
+        let t = crate::thread::spawn(move || {
+            writer.write_all(b"hello pipe").unwrap();
+        })
+        .unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, b"hello pipe");
+        t.join().unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+//! Waiting on several file descriptors at once.
+//!
+//! The kernel has no poll syscall yet, so readiness is probed per fd with a read with a short timeout.
+//! Bytes read by a probe are kept in a buffer of the [`PollFd`], which returns them through [`Read`]
+//! before reading from the fd again, so nothing is lost.
+//! An eventfd only accepts reads of its whole counter, so a probe reads the counter and signals it again.
+//! Once the kernel offers a readiness call, [`poll`] can hand the whole set to it instead.
+
+use core::time::Duration;
+
+use bitflags::bitflags;
+
+use super::{Read, Result};
+use crate::{
+    sync::COUNTER_SIZE,
+    syscalls::{FileDescriptor, SysErrCode, safe},
+};
+
+/// time a single probe may block for, the smallest timeout the kernel supports
+const PROBE_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// the number of bytes a [`PollFd`] reads ahead while probing
+pub const POLL_BUF_SIZE: usize = 64;
+
+bitflags! {
+    /// The readiness of a file descriptor, see [`poll`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct PollFlags: u8 {
+        /// a read will not block, it returns data or EOF
+        const READABLE = 1 << 0;
+        /// the fd accepts writes. a write to a full pipe may still block.
+        const WRITABLE = 1 << 1;
+        /// the fd is invalid or does not support the requested operation. only ever reported, never requested.
+        const ERROR = 1 << 2;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Stream,
+    Event,
+}
+
+/// A file descriptor to wait on, together with the requested and the reported readiness.
+///
+/// Probing a stream reads ahead up to [`POLL_BUF_SIZE`] bytes, so it should be read through the PollFd
+/// for as long as it is polled.
+#[derive(Debug)]
+pub struct PollFd {
+    fd: FileDescriptor,
+    kind: Kind,
+    interest: PollFlags,
+    ready: PollFlags,
+    buf: [u8; POLL_BUF_SIZE],
+    pos: usize,
+    len: usize,
+}
+
+impl PollFd {
+    /// polls a stream, like stdin, a pipe or a file
+    pub const fn new(fd: FileDescriptor, interest: PollFlags) -> Self {
+        Self::with_kind(fd, Kind::Stream, interest)
+    }
+
+    /// Polls an eventfd, which is readable while its counter is non-zero.
+    /// The counter is restored after probing it, but a concurrent waiter may see it as 0 in between.
+    pub const fn event(fd: FileDescriptor, interest: PollFlags) -> Self {
+        Self::with_kind(fd, Kind::Event, interest)
+    }
+
+    const fn with_kind(fd: FileDescriptor, kind: Kind, interest: PollFlags) -> Self {
+        Self {
+            fd,
+            kind,
+            interest,
+            ready: PollFlags::empty(),
+            buf: [0; POLL_BUF_SIZE],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    pub const fn fd(&self) -> FileDescriptor {
+        self.fd
+    }
+
+    pub const fn interest(&self) -> PollFlags {
+        self.interest
+    }
+
+    /// returns the readiness reported by the last call to [`poll`]
+    pub const fn ready(&self) -> PollFlags {
+        self.ready
+    }
+
+    pub const fn is_readable(&self) -> bool {
+        self.ready.contains(PollFlags::READABLE)
+    }
+
+    pub const fn is_writable(&self) -> bool {
+        self.ready.contains(PollFlags::WRITABLE)
+    }
+
+    pub const fn is_error(&self) -> bool {
+        self.ready.contains(PollFlags::ERROR)
+    }
+
+    /// returns the bytes read ahead while probing, which the next reads return first
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[self.pos..self.len]
+    }
+
+    /// Checks whether a read would block, waiting for at most timeout.
+    /// Returns None if timeout elapsed.
+    fn probe_read(&mut self, timeout: Option<Duration>) -> Option<PollFlags> {
+        if !self.buffered().is_empty() {
+            return Some(PollFlags::READABLE);
+        }
+        let res = match self.kind {
+            Kind::Stream => safe::read(self.fd, &mut self.buf, timeout).map(|n| {
+                (self.pos, self.len) = (0, n);
+            }),
+            Kind::Event => {
+                let mut count = [0; COUNTER_SIZE];
+                safe::read(self.fd, &mut count, timeout)
+                    .and_then(|_| safe::write(self.fd, &count))
+                    .map(|_| ())
+            }
+        };
+        match res {
+            Ok(()) => Some(PollFlags::READABLE),
+            Err(SysErrCode::TimedOut) => None,
+            Err(_) => Some(PollFlags::ERROR),
+        }
+    }
+
+    fn probe_write(&self) -> PollFlags {
+        // eventfd writes only block when the counter would overflow, which it never does
+        if self.kind == Kind::Event {
+            return PollFlags::WRITABLE;
+        }
+        match safe::write(self.fd, &[]) {
+            Ok(_) => PollFlags::WRITABLE,
+            Err(_) => PollFlags::ERROR,
+        }
+    }
+}
+
+impl Read for PollFd {
+    /// returns the bytes read ahead while probing first, then reads from the fd
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let buffered = self.buffered();
+        if buffered.is_empty() {
+            return Ok(safe::read(self.fd, buf, None)?);
+        }
+        let n = buffered.len().min(buf.len());
+        buf[..n].copy_from_slice(&buffered[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Waits until at least one of fds is ready for the operations it is interested in, or timeout elapses.
+/// Sets the readiness of every fd and returns the number of ready fds, which is 0 on timeout.
+/// None waits indefinitely.
+///
+/// The timeout is counted in probes, so it may be exceeded by the time spent probing fds,
+/// which is about 1ms for every fd which is not readable.
+pub fn poll(fds: &mut [PollFd], timeout: Option<Duration>) -> Result<usize> {
+    // a single fd can be waited on directly, for as long as the caller wants
+    if let [pfd] = fds
+        && pfd.interest == PollFlags::READABLE
+    {
+        let wait = timeout.map(|t| t.max(PROBE_TIMEOUT));
+        pfd.ready = pfd.probe_read(wait).unwrap_or_default();
+        return Ok(!pfd.ready.is_empty() as usize);
+    }
+
+    let mut waited = Duration::ZERO;
+    loop {
+        let mut ready = 0;
+        let mut probes = 0u32;
+        for pfd in fds.iter_mut() {
+            pfd.ready = PollFlags::empty();
+            if pfd.interest.contains(PollFlags::READABLE) {
+                probes += 1;
+                let readable = pfd.probe_read(Some(PROBE_TIMEOUT));
+                pfd.ready |= readable.unwrap_or_default();
+            }
+            if pfd.interest.contains(PollFlags::WRITABLE) {
+                pfd.ready |= pfd.probe_write();
+            }
+            if !pfd.ready.is_empty() {
+                ready += 1;
+            }
+        }
+        if ready > 0 {
+            return Ok(ready);
+        }
+        if probes == 0 {
+            // nothing blocked, so the time has to pass otherwise
+            safe::waittime(PROBE_TIMEOUT)?;
+        }
+        waited += PROBE_TIMEOUT * probes.max(1);
+        if timeout.is_some_and(|t| waited >= t) {
+            return Ok(0);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readiness() {
+        let [reader, writer] = safe::pipe(None).unwrap();
+        let event = safe::eventfd().unwrap();
+        let mut fds = [
+            PollFd::new(reader, PollFlags::READABLE),
+            PollFd::event(event, PollFlags::READABLE),
+        ];
+        assert_eq!(poll(&mut fds, Some(Duration::from_millis(5))).unwrap(), 0);
+
+        safe::write(event, &3u64.to_ne_bytes()).unwrap();
+        assert_eq!(poll(&mut fds, Some(Duration::from_millis(5))).unwrap(), 1);
+        assert!(!fds[0].is_readable() && fds[1].is_readable());
+        // probing signals the counter again
+        let mut count = [0; COUNTER_SIZE];
+        safe::read(event, &mut count, None).unwrap();
+        assert_eq!(u64::from_ne_bytes(count), 3);
+
+        safe::write(writer, b"xyz").unwrap();
+        assert_eq!(poll(&mut fds, None).unwrap(), 1);
+        assert!(fds[0].is_readable() && !fds[1].is_readable());
+        assert_eq!(fds[0].buffered(), b"xyz");
+        let mut buf = [0; 2];
+        fds[0].read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"xy");
+        // bytes which were read ahead keep the fd readable
+        assert_eq!(poll(&mut fds[..1], Some(Duration::ZERO)).unwrap(), 1);
+        safe::write(writer, b"!").unwrap();
+        let mut buf = [0; 4];
+        assert_eq!(fds[0].read(&mut buf).unwrap(), 1);
+        assert_eq!(fds[0].read(&mut buf).unwrap(), 1);
+        assert_eq!(&buf[..1], b"!");
+
+        let mut fds = [
+            PollFd::new(writer, PollFlags::WRITABLE | PollFlags::READABLE),
+            PollFd::new(FileDescriptor::MAX, PollFlags::READABLE),
+            PollFd::event(event, PollFlags::WRITABLE),
+        ];
+        assert_eq!(poll(&mut fds, None).unwrap(), 3);
+        assert_eq!(fds[0].ready(), PollFlags::WRITABLE | PollFlags::ERROR);
+        assert!(fds[1].is_error());
+        assert!(fds[2].is_writable());
+
+        safe::close(writer).unwrap();
+        let mut fds = [PollFd::new(reader, PollFlags::READABLE)];
+        assert_eq!(poll(&mut fds, None).unwrap(), 1);
+        assert_eq!(fds[0].read(&mut buf).unwrap(), 0);
+
+        for fd in [reader, event] {
+            safe::close(fd).unwrap();
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use core::fmt::{self, Write};
+
+use super::{Error, ErrorKind, Result, stderr, stdout};
+use crate::{
+    syscalls::{FileDescriptor, STDERR_FILENO, STDOUT_FILENO, safe},
+    term::{Color, Style, Styled},
+};
+
+/// the stack buffer size used by the print macros
+pub const PRINT_BUF_SIZE: usize = 256;
+
+const SERIAL_PREFIX: Styled<&str> = Style::new().fg(Color::BrightCyan).paint("[USRINFO]");
+
+#[derive(Debug, Clone, Copy)]
+enum Sink {
+    Fd(FileDescriptor),
+    /// the kernels debug channel, see [`safe::dbg`]
+    Serial,
+}
+
+/// A [`fmt::Write`] adapter, which collects output in a stack buffer of N bytes and writes it out in chunks.
+/// Failed writes are remembered and returned by [`FdWriter::flush`] instead of failing the formatting,
+/// all output after a failed write is discarded.
+/// The buffer is flushed on drop.
+pub struct FdWriter<const N: usize = PRINT_BUF_SIZE> {
+    sink: Sink,
+    buf: [u8; N],
+    len: usize,
+    error: Option<Error>,
+}
+
+impl<const N: usize> FdWriter<N> {
+    pub const fn new(fd: FileDescriptor) -> Self {
+        Self::with_sink(Sink::Fd(fd))
+    }
+
+    /// creates a writer to the kernels debug (serial) channel
+    pub const fn serial() -> Self {
+        Self::with_sink(Sink::Serial)
+    }
+
+    const fn with_sink(sink: Sink) -> Self {
+        // every char has to fit into the buffer
+        const { assert!(N >= 4) };
+        Self {
+            sink,
+            buf: [0; N],
+            len: 0,
+            error: None,
+        }
+    }
+
+    /// Writes out the buffer and returns the first error any write failed with.
+    /// A write which makes no progress fails with [`ErrorKind::WriteZero`].
+    pub fn flush(&mut self) -> Result<()> {
+        self.write_buf();
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn write_buf(&mut self) {
+        let mut written = 0;
+        while self.error.is_none() && written < self.len {
+            let chunk = &self.buf[written..self.len];
+            let res = match self.sink {
+                Sink::Fd(fd) => safe::write(fd, chunk),
+                // the debug channel always takes the whole chunk, which only contains whole chars
+                Sink::Serial => {
+                    safe::dbg(unsafe { str::from_utf8_unchecked(chunk) }).map(|_| chunk.len())
+                }
+            };
+            match res {
+                Ok(0) => {
+                    self.error = Some(Error::new(
+                        ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ))
+                }
+                Ok(n) => written += n,
+                Err(e) => self.error = Some(e.into()),
+            }
+        }
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Write for FdWriter<N> {
+    fn write_str(&mut self, mut s: &str) -> fmt::Result {
+        while !s.is_empty() && self.error.is_none() {
+            // chunks only end at char boundaries
+            let n = s.floor_char_boundary(N - self.len);
+            if n == 0 {
+                self.write_buf();
+                continue;
+            }
+            self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+            self.len += n;
+            s = &s[n..];
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Drop for FdWriter<N> {
+    fn drop(&mut self) {
+        self.write_buf();
+    }
+}
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments<'_>) {
+    let _lock = stdout().lock();
+    let mut w = FdWriter::<PRINT_BUF_SIZE>::new(STDOUT_FILENO);
+    _ = w.write_fmt(args);
+    _ = w.flush();
+}
+
+#[doc(hidden)]
+pub fn _eprint(args: fmt::Arguments<'_>) {
+    let _lock = stderr().lock();
+    let mut w = FdWriter::<PRINT_BUF_SIZE>::new(STDERR_FILENO);
+    _ = w.write_fmt(args);
+    _ = w.flush();
+}
+
+#[doc(hidden)]
+pub fn _serial_print(args: fmt::Arguments<'_>) {
+    let mut w = FdWriter::<PRINT_BUF_SIZE>::serial();
+    _ = write!(w, "{} ", SERIAL_PREFIX);
+    _ = w.write_fmt(args);
+    _ = w.flush();
+}
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked() {
+        let [reader, writer] = safe::pipe(None).unwrap();
+        let mut w = FdWriter::<16>::new(writer);
+        for i in 0..10 {
+            writeln!(w, "line {:02} äöü", i).unwrap();
+        }
+        w.flush().unwrap();
+        drop(w);
+        safe::close(writer).unwrap();
+
+        let mut out = [0; 256];
+        let mut len = 0;
+        loop {
+            match safe::read(reader, &mut out[len..], None).unwrap() {
+                0 => break,
+                n => len += n,
+            }
+        }
+        let out = str::from_utf8(&out[..len]).unwrap();
+        assert_eq!(out.lines().count(), 10);
+        assert_eq!(out.lines().nth(7), Some("line 07 äöü"));
+
+        let mut invalid = FdWriter::<16>::new(FileDescriptor::MAX);
+        invalid.write_str("lost").unwrap();
+        assert!(invalid.flush().is_err());
+    }
+}
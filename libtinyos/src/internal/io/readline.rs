@@ -0,0 +1,540 @@
+//! An interactive line editor, for shells and other programs reading lines from a terminal.
+//!
+//! [`Editor::readline`] reads raw bytes from stdin, decodes control keys and escape sequences into [`Key`]s
+//! and redraws the line on stdout with ANSI sequences after every edit. The terminal is expected not to echo input.
+//! Lines are edited in place (arrows, home/end, backspace/delete, ctrl-k/u/w), earlier lines are recalled
+//! with up/down and tab asks an optional [`Completer`].
+//! The line is assumed to fit on a single row, every char taking one column.
+
+use alloc::{boxed::Box, collections::VecDeque, string::String, vec::Vec};
+use core::fmt::Write as _;
+
+use super::{Error, ErrorKind, Read, Result, Write, stdin, stdout};
+use crate::{
+    fs,
+    path::PathBuf,
+    term::{ClearKind, Control},
+};
+
+/// number of lines kept in the history by default
+pub const DEFAULT_HISTORY_SIZE: usize = 100;
+
+/// A key press decoded from terminal input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    /// ctrl-k, deletes from the cursor to the end of the line
+    KillToEnd,
+    /// ctrl-u, deletes from the start of the line to the cursor
+    KillToStart,
+    /// ctrl-w, deletes the word before the cursor
+    DeleteWord,
+    /// ctrl-l
+    ClearScreen,
+    /// ctrl-c
+    Interrupt,
+    /// ctrl-d
+    Eof,
+    /// any other control key or escape sequence
+    Unknown,
+}
+
+/// Reads the next key from input. Returns None at EOF.
+pub fn read_key<R: Read + ?Sized>(input: &mut R) -> Result<Option<Key>> {
+    let Some(byte) = read_byte(input)? else {
+        return Ok(None);
+    };
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x02 => Key::Left,
+        0x03 => Key::Interrupt,
+        0x04 => Key::Eof,
+        0x05 => Key::End,
+        0x06 => Key::Right,
+        0x0b => Key::KillToEnd,
+        0x0c => Key::ClearScreen,
+        0x0e => Key::Down,
+        0x10 => Key::Up,
+        0x15 => Key::KillToStart,
+        0x17 => Key::DeleteWord,
+        0x1b => read_escape(input)?,
+        0x20..0x7f => Key::Char(byte as char),
+        0x80.. => read_utf8(input, byte)?,
+        _ => Key::Unknown,
+    };
+    Ok(Some(key))
+}
+
+fn read_byte<R: Read + ?Sized>(input: &mut R) -> Result<Option<u8>> {
+    let mut byte = [0];
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// decodes the rest of an escape sequence, after the ESC
+fn read_escape<R: Read + ?Sized>(input: &mut R) -> Result<Key> {
+    match read_byte(input)? {
+        Some(b'[') => {}
+        // SS3 sequences, sent by some terminals for the arrows, home and end
+        Some(b'O') => {
+            return Ok(match read_byte(input)? {
+                Some(b'A') => Key::Up,
+                Some(b'B') => Key::Down,
+                Some(b'C') => Key::Right,
+                Some(b'D') => Key::Left,
+                Some(b'H') => Key::Home,
+                Some(b'F') => Key::End,
+                _ => Key::Unknown,
+            });
+        }
+        _ => return Ok(Key::Unknown),
+    }
+    // a CSI sequence: parameters, then a final byte. only the first parameter matters, modifiers are ignored.
+    let mut param = 0u32;
+    let mut in_first = true;
+    loop {
+        let Some(byte) = read_byte(input)? else {
+            return Ok(Key::Unknown);
+        };
+        match byte {
+            b'0'..=b'9' if in_first => {
+                param = param
+                    .saturating_mul(10)
+                    .saturating_add((byte - b'0') as u32)
+            }
+            b'0'..=b'9' => {}
+            b';' => in_first = false,
+            b'A' => return Ok(Key::Up),
+            b'B' => return Ok(Key::Down),
+            b'C' => return Ok(Key::Right),
+            b'D' => return Ok(Key::Left),
+            b'H' => return Ok(Key::Home),
+            b'F' => return Ok(Key::End),
+            b'~' => {
+                return Ok(match param {
+                    1 | 7 => Key::Home,
+                    3 => Key::Delete,
+                    4 | 8 => Key::End,
+                    _ => Key::Unknown,
+                });
+            }
+            0x40..=0x7e => return Ok(Key::Unknown),
+            _ => {}
+        }
+    }
+}
+
+/// decodes a multi byte utf8 char starting with first
+fn read_utf8<R: Read + ?Sized>(input: &mut R, first: u8) -> Result<Key> {
+    let len = match first {
+        0xc0..0xe0 => 2,
+        0xe0..0xf0 => 3,
+        0xf0..0xf8 => 4,
+        _ => return Ok(Key::Unknown),
+    };
+    let mut buf = [first, 0, 0, 0];
+    for byte in &mut buf[1..len] {
+        match read_byte(input)? {
+            Some(b) => *byte = b,
+            None => return Ok(Key::Unknown),
+        }
+    }
+    Ok(str::from_utf8(&buf[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map_or(Key::Unknown, Key::Char))
+}
+
+/// A source of tab completions, see [`Editor::set_completer`].
+pub trait Completer {
+    /// Returns the candidates for the word before pos (a byte offset into line),
+    /// together with the byte offset at which that word starts.
+    fn complete(&mut self, line: &str, pos: usize) -> (usize, Vec<String>);
+}
+
+impl<F: FnMut(&str, usize) -> (usize, Vec<String>)> Completer for F {
+    fn complete(&mut self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        self(line, pos)
+    }
+}
+
+/// A line editor with a history. See the [module docs](self).
+pub struct Editor {
+    history: VecDeque<String>,
+    max_history: usize,
+    history_file: Option<PathBuf>,
+    completer: Option<Box<dyn Completer>>,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Editor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Editor")
+            .field("history", &self.history)
+            .field("max_history", &self.max_history)
+            .field("history_file", &self.history_file)
+            .finish_non_exhaustive()
+    }
+}
+
+/// the state of the line being edited
+struct Line<'a> {
+    prompt: &'a str,
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl Line<'_> {
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    fn insert(&mut self, s: &str) {
+        for c in s.chars() {
+            self.chars.insert(self.cursor, c);
+            self.cursor += 1;
+        }
+    }
+
+    /// redraws the prompt and the line and places the cursor
+    fn refresh<W: Write + ?Sized>(&self, output: &mut W) -> Result<()> {
+        let mut s = String::new();
+        _ = write!(s, "\r{}", self.prompt);
+        s.extend(&self.chars);
+        _ = write!(s, "{}", Control::Clear(ClearKind::LineToEnd));
+        let back = self.chars.len() - self.cursor;
+        if back > 0 {
+            _ = write!(s, "{}", Control::Back(back.min(u16::MAX as usize) as u16));
+        }
+        output.write_all(s.as_bytes())?;
+        output.flush()
+    }
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            max_history: DEFAULT_HISTORY_SIZE,
+            history_file: None,
+            completer: None,
+        }
+    }
+
+    /// sets the number of lines kept in the history, dropping the oldest ones if necessary
+    pub fn set_max_history(&mut self, max: usize) {
+        self.max_history = max;
+        while self.history.len() > max {
+            self.history.pop_front();
+        }
+    }
+
+    /// returns the history, oldest line first
+    pub fn history(&self) -> impl Iterator<Item = &str> {
+        self.history.iter().map(String::as_str)
+    }
+
+    /// Adds line to the history, unless it is empty or repeats the last line.
+    /// Appends it to the history file, if one is set.
+    pub fn add_history(&mut self, line: &str) -> Result<()> {
+        if line.trim().is_empty() || self.history.back().is_some_and(|last| last == line) {
+            return Ok(());
+        }
+        if self.max_history == 0 {
+            return Ok(());
+        }
+        if self.history.len() == self.max_history {
+            self.history.pop_front();
+        }
+        self.history.push_back(line.into());
+        if let Some(path) = &self.history_file {
+            let mut entry = String::from(line);
+            entry.push('\n');
+            fs::append(path, entry)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the history from the file at path, with one line per entry, and appends new lines to it.
+    /// A missing file is created once the first line is added.
+    pub fn set_history_file<P: Into<PathBuf>>(&mut self, path: P) -> Result<()> {
+        let path = path.into();
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => Some(data),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        if let Some(data) = data {
+            for line in data.lines().filter(|l| !l.is_empty()) {
+                if self.history.len() == self.max_history {
+                    self.history.pop_front();
+                }
+                if self.max_history > 0 {
+                    self.history.push_back(line.into());
+                }
+            }
+        }
+        self.history_file = Some(path);
+        Ok(())
+    }
+
+    /// sets the completer asked for candidates on tab
+    pub fn set_completer<C: Completer + 'static>(&mut self, completer: C) {
+        self.completer = Some(Box::new(completer));
+    }
+
+    /// Reads a line from stdin, printing prompt before it and rendering to stdout.
+    /// See [`Editor::readline_with`].
+    pub fn readline(&mut self, prompt: &str) -> Result<Option<String>> {
+        self.readline_with(prompt, &mut stdin(), &mut stdout().lock())
+    }
+
+    /// Reads and edits a line from input, rendering it to output, until enter is pressed.
+    /// The line is added to the history and returned without the newline.
+    /// Returns None on EOF or ctrl-d on an empty line, and an [`ErrorKind::Interrupted`] error on ctrl-c.
+    pub fn readline_with<R: Read + ?Sized, W: Write + ?Sized>(
+        &mut self,
+        prompt: &str,
+        input: &mut R,
+        output: &mut W,
+    ) -> Result<Option<String>> {
+        let mut line = Line {
+            prompt,
+            chars: Vec::new(),
+            cursor: 0,
+        };
+        // position in the history while navigating it, and the line being edited before
+        let mut history_pos = self.history.len();
+        let mut edited = String::new();
+        line.refresh(output)?;
+        loop {
+            let Some(key) = read_key(input)? else {
+                output.write_all(b"\n")?;
+                if line.chars.is_empty() {
+                    return Ok(None);
+                }
+                break;
+            };
+            match key {
+                Key::Char(c) => {
+                    line.chars.insert(line.cursor, c);
+                    line.cursor += 1;
+                }
+                Key::Enter => {
+                    output.write_all(b"\n")?;
+                    break;
+                }
+                Key::Tab => self.complete(&mut line, output)?,
+                Key::Backspace if line.cursor > 0 => {
+                    line.cursor -= 1;
+                    line.chars.remove(line.cursor);
+                }
+                // ctrl-d deletes like delete, unless the line is empty
+                Key::Delete | Key::Eof if line.cursor < line.chars.len() => {
+                    line.chars.remove(line.cursor);
+                }
+                Key::Left => line.cursor = line.cursor.saturating_sub(1),
+                Key::Right => line.cursor = (line.cursor + 1).min(line.chars.len()),
+                Key::Home => line.cursor = 0,
+                Key::End => line.cursor = line.chars.len(),
+                Key::Up if history_pos > 0 => {
+                    if history_pos == self.history.len() {
+                        edited = line.text();
+                    }
+                    history_pos -= 1;
+                    line.set(&self.history[history_pos]);
+                }
+                Key::Down if history_pos < self.history.len() => {
+                    history_pos += 1;
+                    match self.history.get(history_pos) {
+                        Some(entry) => line.set(entry),
+                        None => line.set(&edited),
+                    }
+                }
+                Key::KillToEnd => line.chars.truncate(line.cursor),
+                Key::KillToStart => {
+                    line.chars.drain(..line.cursor);
+                    line.cursor = 0;
+                }
+                Key::DeleteWord => {
+                    let end = line.cursor;
+                    while line.cursor > 0 && line.chars[line.cursor - 1] == ' ' {
+                        line.cursor -= 1;
+                    }
+                    while line.cursor > 0 && line.chars[line.cursor - 1] != ' ' {
+                        line.cursor -= 1;
+                    }
+                    line.chars.drain(line.cursor..end);
+                }
+                Key::ClearScreen => {
+                    write!(
+                        output,
+                        "{}{}",
+                        Control::Clear(ClearKind::All),
+                        Control::MoveTo { row: 0, col: 0 }
+                    )?;
+                }
+                Key::Interrupt => {
+                    output.write_all(b"^C\n")?;
+                    return Err(Error::new(ErrorKind::Interrupted, "interrupted by ctrl-c"));
+                }
+                Key::Eof if line.chars.is_empty() => {
+                    output.write_all(b"\n")?;
+                    return Ok(None);
+                }
+                _ => {}
+            }
+            line.refresh(output)?;
+        }
+        let text = line.text();
+        self.add_history(&text)?;
+        Ok(Some(text))
+    }
+
+    /// completes the word before the cursor, or lists the candidates if there is nothing to complete
+    fn complete<W: Write + ?Sized>(&mut self, line: &mut Line<'_>, output: &mut W) -> Result<()> {
+        let Some(completer) = &mut self.completer else {
+            return Ok(());
+        };
+        let text = line.text();
+        let pos = text
+            .char_indices()
+            .nth(line.cursor)
+            .map_or(text.len(), |(i, _)| i);
+        let (start, candidates) = completer.complete(&text, pos);
+        let Some(word) = text.get(start.min(pos)..pos) else {
+            return Ok(());
+        };
+        match candidates.as_slice() {
+            [] => {}
+            [single] => {
+                line.insert(single.strip_prefix(word).unwrap_or_default());
+                line.insert(" ");
+            }
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.as_str(), |common, c| {
+                    let len = common
+                        .char_indices()
+                        .zip(c.chars())
+                        .find(|((_, a), b)| a != b)
+                        .map_or(common.len().min(c.len()), |((i, _), _)| i);
+                    &common[..len]
+                });
+                match common.strip_prefix(word) {
+                    Some(more) if !more.is_empty() => line.insert(more),
+                    _ => {
+                        output.write_all(b"\n")?;
+                        for candidate in &candidates {
+                            write!(output, "{}  ", candidate)?;
+                        }
+                        output.write_all(b"\n")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    fn edit(editor: &mut Editor, input: &[u8]) -> Option<String> {
+        editor
+            .readline_with("> ", &mut &input[..], &mut Vec::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn editing() {
+        let mut editor = Editor::new();
+        assert_eq!(
+            edit(&mut editor, b"ab\x1b[D\x1b[DX\x1b[F!\r").as_deref(),
+            Some("Xab!")
+        );
+        assert_eq!(
+            edit(&mut editor, "äbc\x1bOH\x1b[3~\x05\x08\n".as_bytes()).as_deref(),
+            Some("b")
+        );
+        assert_eq!(
+            edit(&mut editor, b"one two\x17\x15x\r").as_deref(),
+            Some("x")
+        );
+        assert_eq!(edit(&mut editor, b"\x04"), None);
+        assert_eq!(edit(&mut editor, b"partial").as_deref(), Some("partial"));
+
+        assert_eq!(
+            edit(&mut editor, b"\x1b[A\x1b[A\x1b[A\x1b[B\r").as_deref(),
+            Some("x")
+        );
+        assert_eq!(editor.history().count(), 5);
+        let err = editor
+            .readline_with("", &mut &b"\x03"[..], &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Interrupted);
+
+        editor.set_completer(|line: &str, pos: usize| {
+            let start = line[..pos].rfind(' ').map_or(0, |i| i + 1);
+            let words = ["help", "hello", "exit"];
+            let matches = words.iter().filter(|w| w.starts_with(&line[start..pos]));
+            (start, matches.map(|w| w.to_string()).collect())
+        });
+        assert_eq!(edit(&mut editor, b"h\tp\r").as_deref(), Some("help"));
+        assert_eq!(edit(&mut editor, b"e\tnow\r").as_deref(), Some("exit now"));
+        let mut out = Vec::new();
+        editor
+            .readline_with("", &mut &b"hel\t\r"[..], &mut out)
+            .unwrap();
+        assert!(str::from_utf8(&out).unwrap().contains("help  hello  \n"));
+        assert_eq!(
+            vec![Key::Delete],
+            [read_key(&mut &b"\x1b[3;5~"[..]).unwrap().unwrap()]
+        );
+    }
+
+    #[cfg(feature = "mock-kernel")]
+    #[test]
+    fn history_file() {
+        crate::syscalls::mock::write_file("/ram/history", b"old\n");
+        let mut editor = Editor::new();
+        editor.set_history_file("/ram/history").unwrap();
+        assert_eq!(edit(&mut editor, b"\x1b[A\r").as_deref(), Some("old"));
+        assert_eq!(edit(&mut editor, b"new\r").as_deref(), Some("new"));
+        assert_eq!(
+            crate::syscalls::mock::read_file("/ram/history").as_deref(),
+            Some(&b"old\nnew\n"[..])
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use super::{Error, ErrorKind, Read, Result, Write};
+use crate::{
+    os::fd::{AsFd, BorrowedFd},
+    sync::{ReentrantMutex, ReentrantMutexGuard},
+    syscalls::{STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, safe},
+};
+
+/// read timeout of stdin in ms, 0 meaning no timeout
+static STDIN_TIMEOUT: AtomicU64 = AtomicU64::new(0);
+
+// the print macros and all handles of the process share these locks
+static STDOUT_LOCK: ReentrantMutex<()> = ReentrantMutex::new(());
+static STDERR_LOCK: ReentrantMutex<()> = ReentrantMutex::new(());
+
+/// A handle to the standard input of the process.
+#[derive(Debug, Clone, Copy)]
+pub struct Stdin {
+    _private: (),
+}
+
+/// A handle to the standard output of the process. Writes are not buffered.
+#[derive(Debug, Clone, Copy)]
+pub struct Stdout {
+    _private: (),
+}
+
+/// A handle to the standard error of the process. Writes are not buffered.
+#[derive(Debug, Clone, Copy)]
+pub struct Stderr {
+    _private: (),
+}
+
+pub fn stdin() -> Stdin {
+    Stdin { _private: () }
+}
+
+pub fn stdout() -> Stdout {
+    Stdout { _private: () }
+}
+
+pub fn stderr() -> Stderr {
+    Stderr { _private: () }
+}
+
+impl Stdin {
+    /// Sets the timeout of reads from stdin, after which they fail with [`ErrorKind::TimedOut`].
+    /// None lets reads block until data is available. The timeout is shared by all handles.
+    /// Returns an error if timeout is zero.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<()> {
+        let ms = match timeout {
+            Some(Duration::ZERO) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "cannot set a zero read timeout",
+                ));
+            }
+            // the kernel counts in ms, so fractions of a ms are rounded up
+            Some(t) => t.as_nanos().div_ceil(1_000_000).min(u64::MAX as u128) as u64,
+            None => 0,
+        };
+        STDIN_TIMEOUT.store(ms, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn read_timeout(&self) -> Option<Duration> {
+        match STDIN_TIMEOUT.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+}
+
+impl AsFd for Stdin {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        BorrowedFd::stdin()
+    }
+}
+
+impl Read for Stdin {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Ok(safe::read(STDIN_FILENO, buf, self.read_timeout())?)
+    }
+}
+
+/// A locked handle to stdout, see [`Stdout::lock`].
+pub struct StdoutLock<'a> {
+    _guard: ReentrantMutexGuard<'a, ()>,
+}
+
+/// A locked handle to stderr, see [`Stderr::lock`].
+pub struct StderrLock<'a> {
+    _guard: ReentrantMutexGuard<'a, ()>,
+}
+
+impl Stdout {
+    /// Locks stdout for the calling thread, so that writes through the lock are not interleaved
+    /// with output of other threads. The lock is reentrant and shared with the print macros.
+    pub fn lock(&self) -> StdoutLock<'static> {
+        StdoutLock {
+            _guard: STDOUT_LOCK.lock(),
+        }
+    }
+}
+
+impl Stderr {
+    /// Locks stderr for the calling thread, so that writes through the lock are not interleaved
+    /// with output of other threads. The lock is reentrant and shared with the eprint macros.
+    pub fn lock(&self) -> StderrLock<'static> {
+        StderrLock {
+            _guard: STDERR_LOCK.lock(),
+        }
+    }
+}
+
+macro_rules! impl_write {
+    ($handle:ident, $lock:ident, $fd:expr, $borrow:expr) => {
+        impl AsFd for $handle {
+            fn as_fd(&self) -> BorrowedFd<'_> {
+                $borrow
+            }
+        }
+
+        impl Write for $lock<'_> {
+            fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                Ok(safe::write($fd, buf)?)
+            }
+
+            fn flush(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        impl Write for $handle {
+            fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                self.lock().write(buf)
+            }
+
+            fn flush(&mut self) -> Result<()> {
+                self.lock().flush()
+            }
+
+            fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+                self.lock().write_all(buf)
+            }
+
+            fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<()> {
+                self.lock().write_fmt(args)
+            }
+        }
+
+        impl fmt::Debug for $lock<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($lock)).finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+impl_write!(Stdout, StdoutLock, STDOUT_FILENO, BorrowedFd::stdout());
+impl_write!(Stderr, StderrLock, STDERR_FILENO, BorrowedFd::stderr());
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+    use crate::syscalls::mock;
+
+    #[test]
+    fn stdin_reads() {
+        let _capture = mock::capture();
+        let mut stdin = stdin();
+        stdin
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        assert_eq!(
+            stdin.read(&mut [0; 4]).map_err(|e| e.kind()),
+            Err(ErrorKind::TimedOut)
+        );
+        stdin
+            .set_read_timeout(Some(Duration::from_micros(1500)))
+            .unwrap();
+        assert_eq!(stdin.read_timeout(), Some(Duration::from_millis(2)));
+        stdin.set_read_timeout(None).unwrap();
+        assert!(stdin.set_read_timeout(Some(Duration::ZERO)).is_err());
+
+        mock::push_stdin(b"ab");
+        let mut buf = [0; 2];
+        stdin.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ab");
+    }
+}
@@ -0,0 +1,306 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+use super::{Chain, Error, ErrorKind, Result, Take};
+
+/// number of bytes read_to_end grows its buffer by, if it is full
+#[cfg(feature = "alloc")]
+const READ_CHUNK: usize = 256;
+
+/// A source of bytes.
+pub trait Read {
+    /// reads some bytes into buf and returns how many were read. 0 signals EOF (or an empty buf).
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    /// reads exactly buf.len() bytes, failing with [`ErrorKind::UnexpectedEof`] if EOF is reached first
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.read(buf) {
+                Ok(0) => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    ));
+                }
+                Ok(n) => buf = &mut buf[n..],
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// reads all bytes until EOF and appends them to buf. returns the number of bytes read.
+    #[cfg(feature = "alloc")]
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        let start = buf.len();
+        let mut filled = start;
+        loop {
+            if filled == buf.len() {
+                // doubling the buffer keeps the number of reads logarithmic, and only the new tail is zeroed.
+                // capacity reserved by the caller is used first.
+                let grow = filled.max(READ_CHUNK).max(buf.capacity() - filled);
+                buf.resize(filled + grow, 0);
+            }
+            match self.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    buf.truncate(filled);
+                    return Err(e);
+                }
+            }
+        }
+        buf.truncate(filled);
+        Ok(filled - start)
+    }
+
+    /// reads all bytes until EOF and appends them to buf, if they are valid utf8.
+    /// returns the number of bytes read.
+    #[cfg(feature = "alloc")]
+    fn read_to_string(&mut self, buf: &mut String) -> Result<usize> {
+        let mut bytes = Vec::new();
+        self.read_to_end(&mut bytes)?;
+        buf.push_str(str::from_utf8(&bytes)?);
+        Ok(bytes.len())
+    }
+
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
+    /// returns a reader which reads at most limit bytes from this reader
+    fn take(self, limit: u64) -> Take<Self>
+    where
+        Self: Sized,
+    {
+        Take::new(self, limit)
+    }
+
+    /// returns a reader which reads from next once this reader reached EOF
+    fn chain<R: Read>(self, next: R) -> Chain<Self, R>
+    where
+        Self: Sized,
+    {
+        Chain::new(self, next)
+    }
+}
+
+/// A sink of bytes.
+pub trait Write {
+    /// writes some bytes of buf and returns how many were written
+    fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+    /// writes out any buffered data
+    fn flush(&mut self) -> Result<()>;
+
+    /// writes all of buf, failing with [`ErrorKind::WriteZero`] if no progress can be made
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf) {
+                Ok(0) => {
+                    return Err(Error::new(
+                        ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ));
+                }
+                Ok(n) => buf = &buf[n..],
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// writes formatted output, used by `write!`
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<()> {
+        struct Adapter<'a, W: ?Sized> {
+            inner: &'a mut W,
+            error: Result<()>,
+        }
+
+        impl<W: Write + ?Sized> fmt::Write for Adapter<'_, W> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.inner.write_all(s.as_bytes()).map_err(|e| {
+                    self.error = Err(e);
+                    fmt::Error
+                })
+            }
+        }
+
+        let mut adapter = Adapter {
+            inner: self,
+            error: Ok(()),
+        };
+        match fmt::write(&mut adapter, args) {
+            Ok(()) => Ok(()),
+            // a formatting trait failed, if the writer did not
+            Err(_) => adapter
+                .error
+                .and(Err(Error::new(ErrorKind::Other, "formatter error"))),
+        }
+    }
+
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+/// A position to seek to, relative to the start, the end or the current position of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    Start(u64),
+    End(i64),
+    Current(i64),
+}
+
+/// A stream with a cursor, which can be moved.
+pub trait Seek {
+    /// moves the cursor to pos and returns the new position from the start of the stream
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+
+    /// moves the cursor to the start of the stream
+    fn rewind(&mut self) -> Result<()> {
+        self.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+
+    /// returns the current position from the start of the stream
+    fn stream_position(&mut self) -> Result<u64> {
+        self.seek(SeekFrom::Current(0))
+    }
+
+    /// returns the length of the stream, leaving the cursor where it is
+    fn stream_len(&mut self) -> Result<u64> {
+        let pos = self.stream_position()?;
+        let len = self.seek(SeekFrom::End(0))?;
+        if pos != len {
+            self.seek(SeekFrom::Start(pos))?;
+        }
+        Ok(len)
+    }
+}
+
+/// returns base moved by offset, failing if the result would be negative or overflow
+pub(crate) fn seek_offset(base: u64, offset: i64) -> Result<u64> {
+    base.checked_add_signed(offset).ok_or(Error::new(
+        ErrorKind::InvalidInput,
+        "invalid seek to a negative or overflowing position",
+    ))
+}
+
+impl<R: Read + ?Sized> Read for &mut R {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+impl<W: Write + ?Sized> Write for &mut W {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        (**self).flush()
+    }
+}
+
+impl<S: Seek + ?Sized> Seek for &mut S {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        (**self).seek(pos)
+    }
+}
+
+impl Read for &[u8] {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = buf.len().min(self.len());
+        let (head, tail) = self.split_at(n);
+        buf[..n].copy_from_slice(head);
+        *self = tail;
+        Ok(n)
+    }
+}
+
+/// writes into the slice, advancing it past the written bytes
+impl Write for &mut [u8] {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = buf.len().min(self.len());
+        let (head, tail) = core::mem::take(self).split_at_mut(n);
+        head.copy_from_slice(&buf[..n]);
+        *self = tail;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Write for Vec<u8> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices() {
+        let mut src: &[u8] = b"hello world";
+        let mut word = [0; 5];
+        src.read_exact(&mut word).unwrap();
+        assert_eq!(&word, b"hello");
+        assert_eq!(
+            src.read_exact(&mut [0; 16]).map_err(|e| e.kind()),
+            Err(ErrorKind::UnexpectedEof)
+        );
+
+        let mut buf = [0; 8];
+        let mut dst = &mut buf[..];
+        write!(dst, "{}-{}", 1, 2).unwrap();
+        assert_eq!(
+            write!(dst, "{}", 123456).map_err(|e| e.kind()),
+            Err(ErrorKind::WriteZero)
+        );
+        assert_eq!(&buf[..5], b"1-212");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn read_to_end_grows_geometrically() {
+        struct Counting<'a>(&'a [u8], usize);
+
+        impl Read for Counting<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+                self.1 += 1;
+                self.0.read(buf)
+            }
+        }
+
+        let data = alloc::vec![7; 100 * READ_CHUNK];
+        let mut reader = Counting(&data, 0);
+        let mut buf = alloc::vec![1];
+        assert_eq!(reader.read_to_end(&mut buf).unwrap(), data.len());
+        assert_eq!(buf.len(), data.len() + 1);
+        assert!(buf[1..].iter().all(|&b| b == 7));
+        // 1 + 256 + 512 + ... covers 100 chunks within 8 reads, plus the one returning EOF
+        assert!(reader.1 <= 9, "{} reads", reader.1);
+    }
+}
@@ -0,0 +1,274 @@
+use super::{BufRead, DEFAULT_BUF_SIZE, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+
+/// Copies all bytes from reader to writer through a stack buffer, until reader reaches EOF.
+/// Returns the number of bytes copied.
+pub fn copy<R: Read + ?Sized, W: Write + ?Sized>(reader: &mut R, writer: &mut W) -> Result<u64> {
+    let mut buf = [0; DEFAULT_BUF_SIZE];
+    let mut copied = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+    }
+}
+
+/// A reader which is always at EOF, see [`empty`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Empty;
+
+/// returns a reader which is always at EOF, and a writer which discards all data
+pub const fn empty() -> Empty {
+    Empty
+}
+
+impl Read for Empty {
+    fn read(&mut self, _buf: &mut [u8]) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+impl BufRead for Empty {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(&[])
+    }
+
+    fn consume(&mut self, _amt: usize) {}
+}
+
+impl Write for Empty {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for Empty {
+    fn seek(&mut self, _pos: SeekFrom) -> Result<u64> {
+        Ok(0)
+    }
+}
+
+/// A writer which discards all data, see [`sink`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sink;
+
+/// returns a writer which discards all data
+pub const fn sink() -> Sink {
+    Sink
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A reader which reads at most a limited number of bytes, see [`Read::take`].
+#[derive(Debug)]
+pub struct Take<R> {
+    inner: R,
+    limit: u64,
+}
+
+impl<R> Take<R> {
+    pub(crate) fn new(inner: R, limit: u64) -> Self {
+        Self { inner, limit }
+    }
+
+    /// returns the number of bytes which can still be read
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: u64) {
+        self.limit = limit;
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Take<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let max = buf.len().min(self.limit.try_into().unwrap_or(usize::MAX));
+        let n = self.inner.read(&mut buf[..max])?;
+        self.limit -= n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Take<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.limit == 0 {
+            return Ok(&[]);
+        }
+        let buf = self.inner.fill_buf()?;
+        let max = buf.len().min(self.limit.try_into().unwrap_or(usize::MAX));
+        Ok(&buf[..max])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = (amt as u64).min(self.limit);
+        self.limit -= amt;
+        self.inner.consume(amt as usize);
+    }
+}
+
+/// A reader which reads from two readers after one another, see [`Read::chain`].
+#[derive(Debug)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+    done_first: bool,
+}
+
+impl<A, B> Chain<A, B> {
+    pub(crate) fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            done_first: false,
+        }
+    }
+
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.first, &mut self.second)
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: Read, B: Read> Read for Chain<A, B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if !self.done_first {
+            match self.first.read(buf)? {
+                0 if !buf.is_empty() => self.done_first = true,
+                n => return Ok(n),
+            }
+        }
+        self.second.read(buf)
+    }
+}
+
+impl<A: BufRead, B: BufRead> BufRead for Chain<A, B> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if !self.done_first {
+            // checked separately, as returning the borrowed buffer directly does not pass the borrow checker
+            if self.first.fill_buf()?.is_empty() {
+                self.done_first = true;
+            } else {
+                return self.first.fill_buf();
+            }
+        }
+        self.second.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.done_first {
+            self.second.consume(amt)
+        } else {
+            self.first.consume(amt)
+        }
+    }
+}
+
+/// A writer which writes everything to two writers, see [`tee`].
+#[derive(Debug)]
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+/// Returns a writer which writes everything to first and second.
+/// Bytes accepted by first are always written completely to second.
+pub fn tee<A: Write, B: Write>(first: A, second: B) -> Tee<A, B> {
+    Tee { first, second }
+}
+
+impl<A, B> Tee<A, B> {
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.first, &mut self.second)
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: Write, B: Write> Write for Tee<A, B> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.first.write(buf)?;
+        self.second.write_all(&buf[..n])?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn adaptors() {
+        let mut reader = (&b"hello "[..]).chain(&b"world, and more"[..]).take(11);
+        let mut out = Vec::new();
+        assert_eq!(copy(&mut reader, &mut out).unwrap(), 11);
+        assert_eq!(out, b"hello world");
+
+        let mut lines = (&b"a\nb"[..]).chain(&b"c\nd"[..]).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), "a");
+        assert_eq!(lines.next().unwrap().unwrap(), "bc");
+        assert_eq!(lines.next().unwrap().unwrap(), "d");
+        assert!(lines.next().is_none());
+
+        let mut first = [0; 4];
+        let mut t = tee(&mut first[..], Vec::new());
+        assert_eq!(
+            copy(&mut &b"abcdef"[..], &mut t).map_err(|e| e.kind()),
+            Err(ErrorKind::WriteZero)
+        );
+        let (_, second) = t.into_inner();
+        assert_eq!(second, b"abcd");
+        assert_eq!(&first, b"abcd");
+
+        assert_eq!(copy(&mut empty(), &mut sink()).unwrap(), 0);
+        assert_eq!(copy(&mut &b"discarded"[..], &mut sink()).unwrap(), 9);
+    }
+}
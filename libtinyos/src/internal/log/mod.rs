@@ -0,0 +1,183 @@
+//! A backend for the [`log`](::log) facade.
+//!
+//! Records are written with the time since boot, their level and their target (usually the module path)
+//! to the kernels debug (serial) channel and/or stderr.
+//! The logger is installed at startup if the `LOG` environment variable is set,
+//! a ',' separated list of a level (`off`, `error`, `warn`, `info`, `debug` or `trace`) and the outputs
+//! `serial` and `stderr`, e.g. `LOG=debug,serial`. Without an output, records go to stderr.
+//! Programs may also install it themselves with [`init`] or [`init_with`].
+
+use core::{
+    fmt::{self, Write},
+    sync::atomic::{AtomicU8, Ordering},
+    time::Duration,
+};
+
+pub use ::log::{
+    Level, LevelFilter, Log, Metadata, Record, SetLoggerError, debug, error, info, log,
+    log_enabled, trace, warn,
+};
+use bitflags::bitflags;
+
+use crate::{
+    internal::rt::try_runtime,
+    io::{FdWriter, PRINT_BUF_SIZE, stderr},
+    syscalls::{STDERR_FILENO, safe},
+};
+
+pub const LOG_VAR: &str = "LOG";
+
+bitflags! {
+    /// Where log records are written to.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LogOutputs: u8 {
+        /// the kernels debug channel, see [`safe::dbg`]
+        const SERIAL = 1 << 0;
+        const STDERR = 1 << 1;
+    }
+}
+
+static LOGGER: Logger = Logger {
+    outputs: AtomicU8::new(LogOutputs::STDERR.bits()),
+};
+
+struct Logger {
+    outputs: AtomicU8,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= ::log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let time = safe::time().unwrap_or_default();
+        if outputs().contains(LogOutputs::SERIAL) {
+            let mut w = FdWriter::<PRINT_BUF_SIZE>::serial();
+            _ = write_record(&mut w, time, record);
+            _ = w.flush();
+        }
+        if outputs().contains(LogOutputs::STDERR) {
+            let _lock = stderr().lock();
+            let mut w = FdWriter::<PRINT_BUF_SIZE>::new(STDERR_FILENO);
+            _ = write_record(&mut w, time, record);
+            _ = w.flush();
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Parses a `LOG` value into a level and the outputs. Unknown entries are ignored.
+/// Returns None if no level is given.
+pub fn parse_config(config: &str) -> Option<(LevelFilter, LogOutputs)> {
+    let mut level = None;
+    let mut outputs = LogOutputs::empty();
+    for entry in config.split(',').map(str::trim) {
+        if entry.eq_ignore_ascii_case("serial") {
+            outputs |= LogOutputs::SERIAL;
+        } else if entry.eq_ignore_ascii_case("stderr") {
+            outputs |= LogOutputs::STDERR;
+        } else if let Ok(l) = entry.parse() {
+            level = Some(l);
+        }
+    }
+    if outputs.is_empty() {
+        outputs = LogOutputs::STDERR;
+    }
+    level.map(|level| (level, outputs))
+}
+
+/// Installs the logger, configured from the `LOG` environment variable.
+/// If it is not set or has no level, only errors are logged, to stderr.
+/// Fails if a logger is already installed.
+pub fn init() -> Result<(), SetLoggerError> {
+    let (level, outputs) = env_config().unwrap_or((LevelFilter::Error, LogOutputs::STDERR));
+    init_with(level, outputs)
+}
+
+/// Installs the logger with the given maximum level and outputs. Fails if a logger is already installed.
+pub fn init_with(level: LevelFilter, outputs: LogOutputs) -> Result<(), SetLoggerError> {
+    ::log::set_logger(&LOGGER)?;
+    set_outputs(outputs);
+    ::log::set_max_level(level);
+    Ok(())
+}
+
+/// changes where the logger writes to. the level can be changed with [`log::set_max_level`](::log::set_max_level).
+pub fn set_outputs(outputs: LogOutputs) {
+    LOGGER.outputs.store(outputs.bits(), Ordering::Relaxed);
+}
+
+pub fn outputs() -> LogOutputs {
+    LogOutputs::from_bits_truncate(LOGGER.outputs.load(Ordering::Relaxed))
+}
+
+fn env_config() -> Option<(LevelFilter, LogOutputs)> {
+    try_runtime()
+        .and_then(|rt| rt.env())
+        .and_then(|env| env.get(LOG_VAR))
+        .and_then(parse_config)
+}
+
+/// installs the logger if `LOG` is set, called once the runtime is initialized
+pub(crate) fn init_from_env() {
+    if let Some((level, outputs)) = env_config() {
+        _ = init_with(level, outputs);
+    }
+}
+
+/// writes a record as `[secs.millis] LEVEL target: message`
+fn write_record(w: &mut impl Write, time: Duration, record: &Record<'_>) -> fmt::Result {
+    writeln!(
+        w,
+        "[{:>5}.{:03}] {:<5} {}: {}",
+        time.as_secs(),
+        time.subsec_millis(),
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::StackBuf;
+
+    #[test]
+    fn config_and_format() {
+        assert_eq!(
+            parse_config("debug, serial"),
+            Some((LevelFilter::Debug, LogOutputs::SERIAL))
+        );
+        assert_eq!(
+            parse_config("stderr,WARN,serial"),
+            Some((LevelFilter::Warn, LogOutputs::all()))
+        );
+        assert_eq!(
+            parse_config("off"),
+            Some((LevelFilter::Off, LogOutputs::STDERR))
+        );
+        assert_eq!(parse_config("serial,verbose"), None);
+
+        let mut line = StackBuf::<128>::new();
+        write_record(
+            &mut line,
+            Duration::from_millis(12_345),
+            &Record::builder()
+                .level(Level::Info)
+                .target("app::net")
+                .args(format_args!("up after {} tries", 3))
+                .build(),
+        )
+        .unwrap();
+        assert_eq!(
+            line.as_str(),
+            "[   12.345] INFO  app::net: up after 3 tries\n"
+        );
+    }
+}
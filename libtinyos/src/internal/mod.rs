@@ -1,13 +1,17 @@
 #[cfg(feature = "alloc")]
 pub mod alloc;
+pub mod backtrace;
 pub mod collections;
 pub mod fs;
 pub mod io;
+pub mod log;
 pub mod os;
+pub mod panic;
 pub mod path;
 pub mod process;
-mod rt;
+pub(crate) mod rt;
 pub mod sync;
+pub mod term;
 #[cfg(feature = "alloc")]
 pub mod thread;
 pub mod time;
@@ -0,0 +1,229 @@
+//! Owned and borrowed file descriptors.
+//!
+//! An [`OwnedFd`] closes its descriptor on drop, a [`BorrowedFd`] is tied to the lifetime of its owner.
+//! Types wrapping a descriptor expose it through [`AsFd`], [`IntoRawFd`] and [`FromRawFd`].
+
+use core::{fmt, marker::PhantomData, mem::ManuallyDrop};
+
+use crate::{
+    io,
+    syscalls::{FileDescriptor, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, safe},
+};
+
+/// An owned file descriptor, which is closed on drop.
+#[repr(transparent)]
+pub struct OwnedFd {
+    fd: FileDescriptor,
+}
+
+/// A borrowed file descriptor, which stays open for at least 'a.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct BorrowedFd<'a> {
+    fd: FileDescriptor,
+    _owner: PhantomData<&'a OwnedFd>,
+}
+
+/// Types which wrap a file descriptor and can lend it out.
+pub trait AsFd {
+    fn as_fd(&self) -> BorrowedFd<'_>;
+}
+
+/// Types which can return their raw file descriptor. Implemented for every [`AsFd`].
+pub trait AsRawFd {
+    fn as_raw_fd(&self) -> FileDescriptor;
+}
+
+/// Types which can give up ownership of their file descriptor.
+pub trait IntoRawFd {
+    /// returns the descriptor without closing it, the caller is responsible for closing it
+    fn into_raw_fd(self) -> FileDescriptor;
+}
+
+/// Types which can take ownership of a raw file descriptor.
+pub trait FromRawFd {
+    /// # Safety
+    /// fd must be open and must not be owned by anything else, as it will be closed by the returned value
+    unsafe fn from_raw_fd(fd: FileDescriptor) -> Self;
+}
+
+impl<'a> BorrowedFd<'a> {
+    /// # Safety
+    /// fd must stay open for 'a
+    pub const unsafe fn borrow_raw(fd: FileDescriptor) -> Self {
+        Self {
+            fd,
+            _owner: PhantomData,
+        }
+    }
+
+    /// returns a borrow of the stdin of the process, which stays open for the rest of the process
+    pub const fn stdin() -> BorrowedFd<'static> {
+        unsafe { BorrowedFd::borrow_raw(STDIN_FILENO) }
+    }
+
+    /// returns a borrow of the stdout of the process, which stays open for the rest of the process
+    pub const fn stdout() -> BorrowedFd<'static> {
+        unsafe { BorrowedFd::borrow_raw(STDOUT_FILENO) }
+    }
+
+    /// returns a borrow of the stderr of the process, which stays open for the rest of the process
+    pub const fn stderr() -> BorrowedFd<'static> {
+        unsafe { BorrowedFd::borrow_raw(STDERR_FILENO) }
+    }
+
+    /// duplicates the descriptor into the lowest free descriptor
+    pub fn try_clone_to_owned(&self) -> io::Result<OwnedFd> {
+        let fd = safe::dup(self.fd, None)?;
+        Ok(OwnedFd { fd })
+    }
+
+    /// Duplicates the descriptor into new, closing whatever was open at new before,
+    /// e.g. to redirect stdout into a pipe. new is not owned by anything afterwards.
+    pub fn dup_to(&self, new: FileDescriptor) -> io::Result<()> {
+        safe::dup(self.fd, Some(new))?;
+        Ok(())
+    }
+}
+
+impl OwnedFd {
+    /// duplicates the descriptor into the lowest free descriptor, both refer to the same open file
+    pub fn try_clone(&self) -> io::Result<Self> {
+        self.as_fd().try_clone_to_owned()
+    }
+
+    /// see [`BorrowedFd::dup_to`]
+    pub fn dup_to(&self, new: FileDescriptor) -> io::Result<()> {
+        self.as_fd().dup_to(new)
+    }
+}
+
+impl Drop for OwnedFd {
+    fn drop(&mut self) {
+        _ = safe::close(self.fd);
+    }
+}
+
+impl AsFd for OwnedFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        unsafe { BorrowedFd::borrow_raw(self.fd) }
+    }
+}
+
+impl AsFd for BorrowedFd<'_> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        *self
+    }
+}
+
+impl<T: AsFd + ?Sized> AsFd for &T {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        (**self).as_fd()
+    }
+}
+
+impl<T: AsFd + ?Sized> AsFd for &mut T {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        (**self).as_fd()
+    }
+}
+
+impl<T: AsFd + ?Sized> AsRawFd for T {
+    fn as_raw_fd(&self) -> FileDescriptor {
+        self.as_fd().fd
+    }
+}
+
+impl IntoRawFd for OwnedFd {
+    fn into_raw_fd(self) -> FileDescriptor {
+        ManuallyDrop::new(self).fd
+    }
+}
+
+impl FromRawFd for OwnedFd {
+    unsafe fn from_raw_fd(fd: FileDescriptor) -> Self {
+        Self { fd }
+    }
+}
+
+impl fmt::Debug for OwnedFd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedFd").field("fd", &self.fd).finish()
+    }
+}
+
+impl fmt::Debug for BorrowedFd<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedFd").field("fd", &self.fd).finish()
+    }
+}
+
+/// Implements [`AsFd`], [`IntoRawFd`], [`FromRawFd`] and the conversions from and into [`OwnedFd`]
+/// for a type which consists of an OwnedFd field named fd.
+/// Any other fields are given as `field: value`, which they are initialized with when converting from a descriptor.
+macro_rules! impl_owned_fd {
+    ($ty:ty $(, $field:ident: $value:expr)* $(,)?) => {
+        impl $crate::os::fd::AsFd for $ty {
+            fn as_fd(&self) -> $crate::os::fd::BorrowedFd<'_> {
+                $crate::os::fd::AsFd::as_fd(&self.fd)
+            }
+        }
+
+        impl $crate::os::fd::IntoRawFd for $ty {
+            fn into_raw_fd(self) -> $crate::syscalls::FileDescriptor {
+                $crate::os::fd::IntoRawFd::into_raw_fd(self.fd)
+            }
+        }
+
+        impl $crate::os::fd::FromRawFd for $ty {
+            unsafe fn from_raw_fd(fd: $crate::syscalls::FileDescriptor) -> Self {
+                Self {
+                    fd: unsafe { $crate::os::fd::FromRawFd::from_raw_fd(fd) },
+                    $($field: $value,)*
+                }
+            }
+        }
+
+        impl From<$crate::os::fd::OwnedFd> for $ty {
+            fn from(fd: $crate::os::fd::OwnedFd) -> Self {
+                Self {
+                    fd,
+                    $($field: $value,)*
+                }
+            }
+        }
+
+        impl From<$ty> for $crate::os::fd::OwnedFd {
+            fn from(value: $ty) -> Self {
+                value.fd
+            }
+        }
+    };
+}
+pub(crate) use impl_owned_fd;
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ownership() {
+        let [reader, writer] = safe::pipe(None).unwrap();
+        let reader = unsafe { OwnedFd::from_raw_fd(reader) };
+        let writer = unsafe { OwnedFd::from_raw_fd(writer) };
+
+        let clone = writer.try_clone().unwrap();
+        assert_ne!(clone.as_raw_fd(), writer.as_raw_fd());
+        drop(writer);
+        safe::write(clone.as_raw_fd(), b"x").unwrap();
+
+        let spare = reader.try_clone().unwrap();
+        let raw = spare.into_raw_fd();
+        clone.dup_to(raw).unwrap();
+        drop(clone);
+        // raw now refers to the write end, so the reader only sees EOF once it is closed
+        assert!(safe::read(reader.as_raw_fd(), &mut [0; 1], None).is_ok());
+        safe::close(raw).unwrap();
+        assert_eq!(safe::read(reader.as_raw_fd(), &mut [0; 1], None), Ok(0));
+    }
+}
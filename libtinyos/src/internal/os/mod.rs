@@ -2,6 +2,8 @@ use core::{slice, str::Utf8Error};
 
 use crate::internal::rt::runtime;
 
+pub mod fd;
+
 // TODO
 // these should really return their own iterator types
 // migth also want some lifetime data?
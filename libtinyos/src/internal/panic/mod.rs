@@ -0,0 +1,154 @@
+use core::{
+    fmt::{self, Display, Write},
+    mem,
+    panic::{Location, PanicInfo},
+    ptr,
+    sync::atomic::{AtomicI64, AtomicPtr, Ordering},
+};
+
+use crate::{
+    syscalls::{STDERR_FILENO, safe},
+    utils::StackBuf,
+};
+
+mod report;
+pub use report::{CRASH_REPORT_DIR, CRASH_REPORTS_VAR, crash_reports_enabled, set_crash_reports};
+
+/// the exit code used for panics, unless changed with [`set_exit_code`]
+pub const DEFAULT_EXIT_CODE: i64 = 2;
+
+const REPORT_LEN: usize = 1024;
+
+/// A panic hook, called with the panic info before the process exits.
+pub type PanicHook = fn(&PanicInfo<'_>);
+
+// null selects default_hook
+static HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+static EXIT_CODE: AtomicI64 = AtomicI64::new(DEFAULT_EXIT_CODE);
+
+/// Registers a custom panic hook, replacing the previous one.
+/// The hook runs before the exit handlers and should not allocate, as the panic may have been caused by an exhausted heap.
+pub fn set_hook(hook: PanicHook) {
+    HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+/// Unregisters the current panic hook and returns it, restoring the default hook.
+pub fn take_hook() -> PanicHook {
+    let hook = HOOK.swap(ptr::null_mut(), Ordering::AcqRel);
+    to_hook(hook)
+}
+
+fn to_hook(hook: *mut ()) -> PanicHook {
+    if hook.is_null() {
+        default_hook
+    } else {
+        // SAFETY: non null values are only ever stored by set_hook
+        unsafe { mem::transmute::<*mut (), PanicHook>(hook) }
+    }
+}
+
+/// Sets the exit code of the process if it panics.
+pub fn set_exit_code(code: i64) {
+    EXIT_CODE.store(code, Ordering::Relaxed);
+}
+
+pub fn exit_code() -> i64 {
+    EXIT_CODE.load(Ordering::Relaxed)
+}
+
+/// The default panic hook. Writes the message and location of the panic and a backtrace to stderr, without allocating.
+pub fn default_hook(info: &PanicInfo<'_>) {
+    let mut report = StackBuf::<REPORT_LEN>::new();
+    _ = write_report(
+        &mut report,
+        safe::get_tid(),
+        info.location(),
+        info.message(),
+    );
+    write_stderr(&report);
+    // the host stack of the mock kernel may not have frame pointers
+    #[cfg(not(feature = "mock-kernel"))]
+    crate::backtrace::print(STDERR_FILENO);
+}
+
+fn write_report(
+    w: &mut dyn Write,
+    tid: u64,
+    location: Option<&Location<'_>>,
+    message: impl Display,
+) -> fmt::Result {
+    write!(w, "thread '{}' panicked", tid)?;
+    if let Some(location) = location {
+        write!(w, " at {}", location)?;
+    }
+    writeln!(w, ":\n{}", message)
+}
+
+// does not take the stderr lock, which may be held by a thread that can no longer release it
+fn write_stderr<const N: usize>(report: &StackBuf<N>) {
+    _ = safe::write(STDERR_FILENO, report.as_bytes());
+    if report.is_truncated() {
+        _ = safe::write(STDERR_FILENO, b"...\n");
+    }
+}
+
+/// Runs the panic hook and exits the process with the panic exit code.
+#[cfg(not(feature = "mock-kernel"))]
+pub(crate) fn handle(info: &PanicInfo<'_>) -> ! {
+    use core::sync::atomic::AtomicBool;
+
+    use crate::backtrace::Frames;
+    use report::write_crash_report;
+
+    static PANICKING: AtomicBool = AtomicBool::new(false);
+
+    if PANICKING.swap(true, Ordering::AcqRel) {
+        // the hook or an exit handler panicked, or another thread is already panicking
+        let mut report = StackBuf::<REPORT_LEN>::new();
+        _ = write!(report, "panicked while processing panic: ");
+        _ = write_report(
+            &mut report,
+            safe::get_tid(),
+            info.location(),
+            info.message(),
+        );
+        write_stderr(&report);
+        safe::exit(exit_code());
+    }
+    to_hook(HOOK.load(Ordering::Acquire))(info);
+    if crash_reports_enabled() {
+        let mut msg = StackBuf::<REPORT_LEN>::new();
+        match write_crash_report(info.location(), &info.message(), Some(Frames::capture())) {
+            Ok(path) => _ = writeln!(msg, "crash report written to {}", path.as_str()),
+            Err(e) => _ = writeln!(msg, "failed to write crash report: {}", e),
+        }
+        write_stderr(&msg);
+    }
+    crate::process::exit(exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks() {
+        fn quiet(_: &PanicInfo<'_>) {}
+
+        assert!(HOOK.load(Ordering::Relaxed).is_null());
+        set_hook(quiet);
+        assert!(!HOOK.load(Ordering::Relaxed).is_null());
+        take_hook();
+        assert!(HOOK.load(Ordering::Relaxed).is_null());
+    }
+
+    #[test]
+    fn report() {
+        let location = Location::caller();
+        let mut report = StackBuf::<128>::new();
+        write_report(&mut report, 3, Some(location), "oh no").unwrap();
+        let mut expected = StackBuf::<128>::new();
+        writeln!(expected, "thread '3' panicked at {}:\noh no", location).unwrap();
+        assert_eq!(report.as_str(), expected.as_str());
+    }
+}
@@ -0,0 +1,128 @@
+use core::{
+    fmt::{self, Display, Write},
+    panic::Location,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    backtrace::{self, Frames},
+    internal::rt::try_runtime,
+    io::{self, FdWriter},
+    syscalls::{OpenOptions, safe},
+    utils::StackBuf,
+};
+
+/// environment variable enabling crash reports, if set to anything but `0`
+pub const CRASH_REPORTS_VAR: &str = "CRASH_REPORTS";
+/// directory crash reports are written to, as `crash-<pid>.txt`
+pub const CRASH_REPORT_DIR: &str = "/ram";
+
+const PATH_LEN: usize = 64;
+const REPORT_BUF_SIZE: usize = 512;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables writing a crash report to [`CRASH_REPORT_DIR`] when the process panics.
+/// Crash reports are also enabled by the [`CRASH_REPORTS_VAR`] environment variable.
+pub fn set_crash_reports(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn crash_reports_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+        || try_runtime()
+            .and_then(|rt| rt.env())
+            .and_then(|env| env.get(CRASH_REPORTS_VAR))
+            .is_some_and(|v| v != "0")
+}
+
+/// writes a crash report for a panic and returns its path
+// only called from the panic handler, which does not exist with the mock kernel
+#[cfg_attr(feature = "mock-kernel", allow(dead_code))]
+pub(crate) fn write_crash_report(
+    location: Option<&Location<'_>>,
+    message: &dyn Display,
+    frames: Option<Frames>,
+) -> io::Result<StackBuf<PATH_LEN>> {
+    let mut path = StackBuf::<PATH_LEN>::new();
+    _ = write!(path, "{}/crash-{}.txt", CRASH_REPORT_DIR, safe::get_pid()?);
+    let fd = safe::open(
+        path.as_str(),
+        OpenOptions::WRITE | OpenOptions::CREATE | OpenOptions::TRUNCATE,
+    )?;
+    let mut w = FdWriter::<REPORT_BUF_SIZE>::new(fd);
+    _ = write_report(&mut w, location, message, frames);
+    let res = w.flush();
+    drop(w);
+    _ = safe::close(fd);
+    res?;
+    Ok(path)
+}
+
+fn write_report(
+    w: &mut dyn Write,
+    location: Option<&Location<'_>>,
+    message: &dyn Display,
+    frames: Option<Frames>,
+) -> fmt::Result {
+    writeln!(w, "=== crash report ===")?;
+    writeln!(w, "message: {}", message)?;
+    match location {
+        Some(location) => writeln!(w, "location: {}", location)?,
+        None => writeln!(w, "location: unknown")?,
+    }
+    if let Ok(time) = safe::time() {
+        writeln!(w, "time: {}ms", time.as_millis())?;
+    }
+    match safe::get_pid() {
+        Ok(pid) => writeln!(w, "pid: {}", pid)?,
+        Err(e) => writeln!(w, "pid: {:?}", e)?,
+    }
+    writeln!(w, "tid: {}", safe::get_tid())?;
+    writeln!(w, "pgrid: {}", safe::get_pgrid())?;
+
+    let rt = try_runtime();
+    match rt.and_then(|rt| rt.args()) {
+        Some(args) => writeln!(w, "args: {}", args.as_str())?,
+        None => writeln!(w, "args: unavailable")?,
+    }
+    match rt.and_then(|rt| rt.env()) {
+        Some(env) => {
+            writeln!(w, "env:")?;
+            for (key, value) in env.as_split_str().filter(|(key, _)| !key.is_empty()) {
+                writeln!(w, "    {}={}", key, value)?;
+            }
+        }
+        None => writeln!(w, "env: unavailable")?,
+    }
+
+    #[cfg(feature = "alloc")]
+    match crate::tiny_alloc::alloc_data() {
+        Some(heap) => writeln!(w, "heap: used={} free={}", heap.used(), heap.free())?,
+        None => writeln!(w, "heap: unavailable")?,
+    }
+    #[cfg(not(feature = "alloc"))]
+    writeln!(w, "heap: unavailable")?;
+
+    match frames {
+        Some(frames) => backtrace::write_frames(w, frames),
+        None => writeln!(w, "stack backtrace: unavailable"),
+    }
+}
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+    use crate::syscalls::mock;
+
+    #[test]
+    fn report_file() {
+        let path = write_crash_report(Some(Location::caller()), &"boom", None).unwrap();
+        assert!(path.as_str().starts_with("/ram/crash-"));
+        let report = mock::read_file(path.as_str()).unwrap();
+        let report = str::from_utf8(&report).unwrap();
+        assert!(report.starts_with("=== crash report ===\nmessage: boom\nlocation: "));
+        assert!(report.contains("\ntid: "));
+        assert!(report.ends_with("stack backtrace: unavailable\n"));
+    }
+}
@@ -140,6 +140,12 @@ mod alloc_ {
         }
     }
 
+    impl AsRef<Path> for PathBuf {
+        fn as_ref(&self) -> &Path {
+            self
+        }
+    }
+
     impl Deref for PathBuf {
         type Target = Path;
 
@@ -199,7 +205,17 @@ impl Path {
     }
 
     pub fn parent(&self) -> Option<&Path> {
-        self.inner.rsplit_once(PATH_SEP).map(|(s, _)| Path::new(s))
+        if &self.inner == ROOT_DIR {
+            return None;
+        }
+        self.inner.rsplit_once(PATH_SEP).map(|(s, _)| {
+            // the parent of /foo is ROOT_DIR, not the empty str before the first SEP
+            if s.is_empty() && !self.is_relative() {
+                Path::new(ROOT_DIR)
+            } else {
+                Path::new(s)
+            }
+        })
     }
 
     pub fn extension(&self) -> &str {
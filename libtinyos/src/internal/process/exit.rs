@@ -0,0 +1,153 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{io, sync::Mutex, syscalls::safe};
+
+/// the maximum number of handlers which can be registered with at_exit
+pub const MAX_EXIT_HANDLERS: usize = 32;
+
+#[derive(Clone, Copy)]
+enum ExitHandler {
+    Rust(fn()),
+    C(extern "C" fn()),
+}
+
+impl ExitHandler {
+    fn call(self) {
+        match self {
+            Self::Rust(f) => f(),
+            Self::C(f) => f(),
+        }
+    }
+}
+
+struct ExitHandlers {
+    handlers: [Option<ExitHandler>; MAX_EXIT_HANDLERS],
+    len: usize,
+}
+
+impl ExitHandlers {
+    const fn new() -> Self {
+        Self {
+            handlers: [None; MAX_EXIT_HANDLERS],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, handler: ExitHandler) -> io::Result<()> {
+        let slot = self.handlers.get_mut(self.len).ok_or(io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            "too many exit handlers registered",
+        ))?;
+        *slot = Some(handler);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<ExitHandler> {
+        self.len = self.len.checked_sub(1)?;
+        self.handlers[self.len].take()
+    }
+}
+
+static EXIT_HANDLERS: Mutex<ExitHandlers> = Mutex::new(ExitHandlers::new());
+static EXITING: AtomicBool = AtomicBool::new(false);
+
+/// Registers f to be called when the process exits through [`exit`],
+/// by returning from main or by panicking.
+/// Handlers are called in the reverse order of their registration.
+/// At most [`MAX_EXIT_HANDLERS`] handlers can be registered.
+pub fn at_exit(f: fn()) -> io::Result<()> {
+    EXIT_HANDLERS.lock().push(ExitHandler::Rust(f))
+}
+
+pub(crate) fn at_exit_c(f: extern "C" fn()) -> io::Result<()> {
+    EXIT_HANDLERS.lock().push(ExitHandler::C(f))
+}
+
+/// Runs all exit handlers and terminates the process with code.
+/// stdout and stderr are unbuffered, so nothing written to them is lost.
+/// Buffered writers are not flushed, as they are never dropped, so their owners should flush them,
+/// e.g. from an exit handler.
+/// If called again while the handlers are running (e.g. from a handler or a panic in a handler),
+/// the process is terminated immediately.
+pub fn exit(code: i64) -> ! {
+    if !EXITING.swap(true, Ordering::AcqRel) {
+        shutdown();
+    }
+    safe::exit(code)
+}
+
+fn shutdown() {
+    run_exit_handlers();
+    wait_for_std_streams();
+}
+
+/// lets writes of other threads to stdout and stderr finish, by taking their locks.
+/// both are unbuffered, so there is nothing to flush.
+fn wait_for_std_streams() {
+    drop(io::stdout().lock());
+    drop(io::stderr().lock());
+}
+
+fn run_exit_handlers() {
+    // the lock is released before calling a handler, as it may register further handlers
+    while let Some(handler) = { EXIT_HANDLERS.lock().pop() } {
+        handler.call();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::sync::atomic::AtomicUsize;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn first() {
+        CALLS.store(CALLS.load(Ordering::Relaxed) * 10 + 1, Ordering::Relaxed);
+    }
+
+    extern "C" fn second() {
+        CALLS.store(CALLS.load(Ordering::Relaxed) * 10 + 2, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn handlers_run_in_reverse() {
+        let mut handlers = ExitHandlers::new();
+        handlers.push(ExitHandler::Rust(first)).unwrap();
+        handlers.push(ExitHandler::C(second)).unwrap();
+        while let Some(handler) = handlers.pop() {
+            handler.call();
+        }
+        assert_eq!(CALLS.load(Ordering::Relaxed), 21);
+
+        for _ in 0..MAX_EXIT_HANDLERS {
+            handlers.push(ExitHandler::Rust(first)).unwrap();
+        }
+        assert_eq!(
+            handlers
+                .push(ExitHandler::Rust(first))
+                .map_err(|e| e.kind()),
+            Err(io::ErrorKind::OutOfMemory)
+        );
+    }
+
+    #[cfg(feature = "mock-kernel")]
+    #[test]
+    fn output_survives_shutdown() {
+        fn goodbye() {
+            crate::print!("goodbye from an exit handler\n");
+        }
+
+        let _capture = crate::syscalls::mock::capture();
+        at_exit(goodbye).unwrap();
+        shutdown();
+        let out = crate::syscalls::mock::take_stdout();
+        assert!(
+            str::from_utf8(&out)
+                .unwrap()
+                .contains("goodbye from an exit handler\n")
+        );
+    }
+}
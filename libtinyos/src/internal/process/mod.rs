@@ -1,7 +1,141 @@
+use core::{convert::Infallible, fmt::Debug, fmt::Write};
+
+mod exit;
+mod spawn;
+pub(crate) use exit::at_exit_c;
+pub use exit::{MAX_EXIT_HANDLERS, at_exit, exit};
+pub use spawn::{Stdio, spawn};
+
 use tinyos_abi::types::SysErrCode;
 
+use crate::{
+    io,
+    syscalls::{STDERR_FILENO, safe},
+    utils::StackBuf,
+};
+
+/// Declares the entry point of a program using the libtinyos runtime.
+/// The function may return any type implementing [`Termination`],
+/// e.g. `()`, [`ExitCode`] or `Result<(), E: Debug>`.
+/// Programs with the old `#[no_mangle] fn main` still run through a deprecated fallback until they switch to this macro.
+///
+/// ```ignore
+/// fn main() -> Result<(), libtinyos::io::Error> {
+///     Ok(())
+/// }
+///
+/// libtinyos::entry!(main);
+/// ```
+#[macro_export]
+macro_rules! entry {
+    ($main:path) => {
+        #[unsafe(no_mangle)]
+        fn __libtinyos_main() -> $crate::process::ExitCode {
+            $crate::process::Termination::report($main())
+        }
+    };
+}
+
 // TODO
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ProcessError {
     Sys(SysErrCode),
+    Io(io::Error),
+}
+
+impl From<SysErrCode> for ProcessError {
+    fn from(value: SysErrCode) -> Self {
+        Self::Sys(value)
+    }
+}
+
+impl From<io::Error> for ProcessError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// The status a process exits with.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ExitCode(i64);
+
+impl ExitCode {
+    pub const SUCCESS: Self = Self(0);
+    pub const FAILURE: Self = Self(1);
+
+    pub const fn new(code: i64) -> Self {
+        Self(code)
+    }
+
+    pub const fn to_i64(self) -> i64 {
+        self.0
+    }
+}
+
+impl From<u8> for ExitCode {
+    fn from(value: u8) -> Self {
+        Self(value as i64)
+    }
+}
+
+impl From<i64> for ExitCode {
+    fn from(value: i64) -> Self {
+        Self(value)
+    }
+}
+
+/// Values which may be returned from the entry point of a program, see [`entry`](crate::entry).
+pub trait Termination {
+    /// reports the outcome of the program and converts it into the status it exits with
+    fn report(self) -> ExitCode;
+}
+
+impl Termination for () {
+    fn report(self) -> ExitCode {
+        ExitCode::SUCCESS
+    }
+}
+
+impl Termination for ExitCode {
+    fn report(self) -> ExitCode {
+        self
+    }
+}
+
+impl Termination for Infallible {
+    fn report(self) -> ExitCode {
+        match self {}
+    }
+}
+
+impl<T: Termination, E: Debug> Termination for Result<T, E> {
+    fn report(self) -> ExitCode {
+        match self {
+            Ok(val) => val.report(),
+            Err(e) => {
+                // formatted on the stack, as the error may well be that the heap is exhausted
+                let mut buf = StackBuf::<512>::new();
+                _ = writeln!(buf, "Error: {:?}", e);
+                _ = safe::write(STDERR_FILENO, buf.as_bytes());
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn termination() {
+        assert_eq!(().report(), ExitCode::SUCCESS);
+        assert_eq!(ExitCode::from(3u8).report(), ExitCode::new(3));
+        assert_eq!(Ok::<_, ProcessError>(()).report(), ExitCode::SUCCESS);
+        assert_eq!(
+            Ok::<_, ProcessError>(ExitCode::new(7)).report(),
+            ExitCode::new(7)
+        );
+        assert_eq!(Err::<(), _>("boom").report(), ExitCode::FAILURE);
+    }
 }
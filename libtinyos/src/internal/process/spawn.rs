@@ -0,0 +1,73 @@
+use tinyos_abi::types::FDAction;
+
+use crate::{
+    io::{self, PipeReader, PipeWriter},
+    os::fd::{AsRawFd, OwnedFd},
+    path::Path,
+    syscalls::{STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, safe},
+};
+
+/// What a standard stream of a spawned process is connected to, see [`spawn`].
+/// Owns the descriptor it was created from.
+#[derive(Debug)]
+pub struct Stdio {
+    fd: Option<OwnedFd>,
+}
+
+impl Stdio {
+    /// the child shares the stream of this process
+    pub const fn inherit() -> Self {
+        Self { fd: None }
+    }
+}
+
+impl Default for Stdio {
+    fn default() -> Self {
+        Self::inherit()
+    }
+}
+
+impl From<OwnedFd> for Stdio {
+    fn from(fd: OwnedFd) -> Self {
+        Self { fd: Some(fd) }
+    }
+}
+
+impl From<PipeReader> for Stdio {
+    fn from(reader: PipeReader) -> Self {
+        OwnedFd::from(reader).into()
+    }
+}
+
+impl From<PipeWriter> for Stdio {
+    fn from(writer: PipeWriter) -> Self {
+        OwnedFd::from(writer).into()
+    }
+}
+
+/// Spawns the program at path as a new process and returns its pid.
+/// args and env are formatted as in [`safe::execve`].
+/// The stdin, stdout and stderr of the child are connected to the given [`Stdio`]s,
+/// whose descriptors are closed in this process afterwards, so EOF propagates once the child closes its end.
+pub fn spawn<P: AsRef<Path> + ?Sized>(
+    path: &P,
+    args: &str,
+    env: &str,
+    stdin: Stdio,
+    stdout: Stdio,
+    stderr: Stdio,
+) -> io::Result<u64> {
+    let mut actions = [FDAction::Close(STDIN_FILENO); 3];
+    let mut len = 0;
+    for (stdio, slot) in [
+        (&stdin, STDIN_FILENO),
+        (&stdout, STDOUT_FILENO),
+        (&stderr, STDERR_FILENO),
+    ] {
+        if let Some(fd) = &stdio.fd {
+            actions[len] = FDAction::Dup(fd.as_raw_fd(), slot);
+            len += 1;
+        }
+    }
+    Ok(safe::spawn_process(path, args, env, &actions[..len])?)
+}
@@ -2,18 +2,23 @@ use conquer_once::spin::OnceCell;
 
 use crate::{
     internal::os::{EnvVars, ProcessArgs},
-    process::ProcessError,
-    syscalls,
+    process::{self, ExitCode, ProcessError, Termination},
+    syscalls::{STDERR_FILENO, safe},
 };
 
 static RUNTIME: OnceCell<RuntimeData> = OnceCell::uninit();
 
 pub(crate) fn runtime<'a>() -> &'a RuntimeData {
     RUNTIME.get().expect(
-        "Runtime data may only be used if the runtime is used. Consider linking against libtinyos::_start.",
+        "Runtime data may only be used if the runtime is used. Consider linking against libtinyos::_start and declaring main with libtinyos::entry!.",
     )
 }
 
+/// like runtime, but returns None instead of panicking if the runtime is not initialized (yet)
+pub(crate) fn try_runtime<'a>() -> Option<&'a RuntimeData> {
+    RUNTIME.get()
+}
+
 pub(crate) struct RuntimeData {
     args: Option<ProcessArgs>,
     env: Option<EnvVars>,
@@ -42,15 +47,44 @@ impl RuntimeData {
 unsafe impl Sync for RuntimeData {}
 unsafe impl Send for RuntimeData {}
 
+/// initializes the runtime data and installs the logger if configured. only the first call has an effect.
+pub(crate) fn init(argc: usize, argv: *const u8, envc: usize, envp: *const u8) {
+    RUNTIME.init_once(|| RuntimeData::new(argc, argv, envc, envp));
+    crate::log::init_from_env();
+}
+
+#[cfg(not(feature = "mock-kernel"))]
 #[unsafe(no_mangle)]
 pub extern "C" fn _start(argc: usize, argv: *const u8, envc: usize, envp: *const u8) -> ! {
-    RUNTIME.init_once(|| RuntimeData::new(argc, argv, envc, envp));
+    init(argc, argv, envc, envp);
 
-    unsafe { main() }.unwrap();
+    let code = __libtinyos_main();
 
-    unsafe { syscalls::exit(0) }
+    process::exit(code.to_i64())
 }
 
-unsafe extern "Rust" {
-    fn main() -> Result<(), ProcessError>;
+/// The entry point of programs which do not use libtinyos::entry!, which overrides it.
+/// It calls the `#[no_mangle] fn main() -> Result<(), ProcessError>` the runtime used to require.
+/// This fallback is deprecated and will be removed, programs should declare main with libtinyos::entry!.
+#[cfg(not(feature = "mock-kernel"))]
+#[unsafe(no_mangle)]
+#[linkage = "weak"]
+fn __libtinyos_main() -> ExitCode {
+    unsafe extern "Rust" {
+        // null unless the program defines the old main
+        #[linkage = "extern_weak"]
+        #[link_name = "main"]
+        static OLD_MAIN: *const ();
+    }
+
+    let main = unsafe { OLD_MAIN };
+    if main.is_null() {
+        _ = safe::write(
+            STDERR_FILENO,
+            b"no entry point, declare main with libtinyos::entry!\n",
+        );
+        return ExitCode::FAILURE;
+    }
+    let main: fn() -> Result<(), ProcessError> = unsafe { core::mem::transmute(main) };
+    main().report()
 }
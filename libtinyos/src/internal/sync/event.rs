@@ -0,0 +1,92 @@
+use core::time::Duration;
+
+use crate::{
+    io::{Error, ErrorKind, Result},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, impl_owned_fd},
+    syscalls::safe,
+};
+
+/// size of the counter of an eventfd, reads and writes of any other size are rejected
+pub(crate) const COUNTER_SIZE: usize = size_of::<u64>();
+
+/// The smallest timeout the kernel supports, used by [`EventFd::try_wait`].
+const MIN_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// An owned eventfd, a kernel counter which can be signaled and waited on by threads and processes.
+/// [`EventFd::signal`] adds to the counter, waiting blocks until it is non-zero, then returns and resets it.
+/// The descriptor is closed on drop.
+#[derive(Debug)]
+pub struct EventFd {
+    fd: OwnedFd,
+}
+
+impl EventFd {
+    /// creates a new eventfd with a counter of 0
+    pub fn new() -> Result<Self> {
+        let fd = safe::eventfd()?;
+        Ok(unsafe { Self::from_raw_fd(fd) })
+    }
+
+    /// adds n to the counter, waking a waiter. the counter saturates at u64::MAX.
+    pub fn signal(&self, n: u64) -> Result<()> {
+        safe::write(self.fd.as_raw_fd(), &n.to_ne_bytes())?;
+        Ok(())
+    }
+
+    /// blocks until the counter is non-zero, then returns it and resets it to 0
+    pub fn wait(&self) -> Result<u64> {
+        self.read(None)
+    }
+
+    /// like [`EventFd::wait`], but returns None if the counter stays 0 for timeout
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<Option<u64>> {
+        match self.read(Some(timeout)) {
+            Ok(count) => Ok(Some(count)),
+            Err(e) if e.kind() == ErrorKind::TimedOut => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns and resets the counter if it is non-zero, None otherwise.
+    /// As the kernel has no non-blocking reads, this may wait for up to 1ms.
+    pub fn try_wait(&self) -> Result<Option<u64>> {
+        self.wait_timeout(MIN_TIMEOUT)
+    }
+
+    fn read(&self, timeout: Option<Duration>) -> Result<u64> {
+        let mut count = [0; COUNTER_SIZE];
+        if safe::read(self.fd.as_raw_fd(), &mut count, timeout)? != COUNTER_SIZE {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "eventfd returned a partial counter",
+            ));
+        }
+        Ok(u64::from_ne_bytes(count))
+    }
+}
+
+impl_owned_fd!(EventFd);
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts() {
+        let event = EventFd::new().unwrap();
+        assert_eq!(event.try_wait().unwrap(), None);
+        event.signal(2).unwrap();
+        event.signal(3).unwrap();
+        assert_eq!(event.wait().unwrap(), 5);
+        assert_eq!(event.wait_timeout(Duration::from_millis(5)).unwrap(), None);
+        event.signal(u64::MAX).unwrap();
+        event.signal(1).unwrap();
+        assert_eq!(event.try_wait().unwrap(), Some(u64::MAX));
+
+        let [reader, writer] = safe::pipe(None).unwrap();
+        safe::write(writer, b"abc").unwrap();
+        let partial = unsafe { EventFd::from_raw_fd(reader) };
+        assert_eq!(partial.wait().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        safe::close(writer).unwrap();
+    }
+}
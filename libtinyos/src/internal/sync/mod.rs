@@ -1 +1,250 @@
-// TODO
+use core::{
+    cell::{Cell, UnsafeCell},
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use crate::syscalls::safe;
+
+mod event;
+pub(crate) use event::COUNTER_SIZE;
+pub use event::EventFd;
+
+/// number of spins before the lock yields the cpu to other tasks
+const SPINS_BEFORE_YIELD: usize = 64;
+
+/// spins once, yielding the cpu every SPINS_BEFORE_YIELD spins
+fn backoff(spins: &mut usize) {
+    *spins += 1;
+    if spins.is_multiple_of(SPINS_BEFORE_YIELD) {
+        safe::yield_now();
+    } else {
+        core::hint::spin_loop();
+    }
+}
+
+/// A spinning mutual exclusion lock, which yields to the scheduler while contended.
+/// Does not allocate and can be used in statics.
+pub struct Mutex<T: ?Sized> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: access to data is synchronized through locked
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        let mut spins = 0;
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+            while self.locked.load(Ordering::Relaxed) {
+                backoff(&mut spins);
+            }
+        }
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| MutexGuard { lock: self })
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for Mutex<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.try_lock() {
+            Some(guard) => f.debug_struct("Mutex").field("data", &&*guard).finish(),
+            None => f.write_str("Mutex { <locked> }"),
+        }
+    }
+}
+
+pub struct MutexGuard<'a, T: ?Sized> {
+    lock: &'a Mutex<T>,
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+/// owner of an unlocked ReentrantMutex
+const NO_OWNER: u64 = u64::MAX;
+
+/// A spinning lock, which can be locked multiple times by the thread holding it.
+/// Only gives shared access to its data, use a [`core::cell::RefCell`] or similar for mutation.
+pub struct ReentrantMutex<T: ?Sized> {
+    /// tid of the thread holding the lock
+    owner: AtomicU64,
+    /// number of guards of the owner, only accessed by the owner
+    count: Cell<usize>,
+    data: T,
+}
+
+// SAFETY: data is only accessed by the thread holding the lock, count is only accessed by the owner
+unsafe impl<T: ?Sized + Send> Sync for ReentrantMutex<T> {}
+unsafe impl<T: ?Sized + Send> Send for ReentrantMutex<T> {}
+
+impl<T> ReentrantMutex<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            owner: AtomicU64::new(NO_OWNER),
+            count: Cell::new(0),
+            data,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+}
+
+impl<T: ?Sized> ReentrantMutex<T> {
+    pub fn lock(&self) -> ReentrantMutexGuard<'_, T> {
+        let tid = safe::get_tid();
+        let mut spins = 0;
+        loop {
+            if let Some(guard) = self.try_lock_as(tid) {
+                return guard;
+            }
+            while self.owner.load(Ordering::Relaxed) != NO_OWNER {
+                backoff(&mut spins);
+            }
+        }
+    }
+
+    pub fn try_lock(&self) -> Option<ReentrantMutexGuard<'_, T>> {
+        self.try_lock_as(safe::get_tid())
+    }
+
+    fn try_lock_as(&self, tid: u64) -> Option<ReentrantMutexGuard<'_, T>> {
+        if self.owner.load(Ordering::Relaxed) == tid {
+            self.count.set(
+                self.count
+                    .get()
+                    .checked_add(1)
+                    .expect("lock count overflow in reentrant mutex"),
+            );
+        } else {
+            self.owner
+                .compare_exchange(NO_OWNER, tid, Ordering::Acquire, Ordering::Relaxed)
+                .ok()?;
+            self.count.set(1);
+        }
+        Some(ReentrantMutexGuard { lock: self })
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
+
+impl<T: Default> Default for ReentrantMutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for ReentrantMutex<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.try_lock() {
+            Some(guard) => f
+                .debug_struct("ReentrantMutex")
+                .field("data", &&*guard)
+                .finish(),
+            None => f.write_str("ReentrantMutex { <locked> }"),
+        }
+    }
+}
+
+pub struct ReentrantMutexGuard<'a, T: ?Sized> {
+    lock: &'a ReentrantMutex<T>,
+}
+
+impl<T: ?Sized> Deref for ReentrantMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.lock.data
+    }
+}
+
+impl<T: ?Sized> Drop for ReentrantMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let count = self.lock.count.get() - 1;
+        self.lock.count.set(count);
+        if count == 0 {
+            self.lock.owner.store(NO_OWNER, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock-kernel", feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::thread;
+
+    #[test]
+    fn reentrant() {
+        static LOCK: ReentrantMutex<u32> = ReentrantMutex::new(7);
+
+        let outer = LOCK.lock();
+        let inner = LOCK.try_lock().unwrap();
+        assert_eq!(*inner, 7);
+        drop(outer);
+        let other = thread::spawn(|| LOCK.try_lock().is_some()).unwrap();
+        assert!(!other.join().unwrap());
+
+        drop(inner);
+        let other = thread::spawn(|| LOCK.try_lock().is_some()).unwrap();
+        assert!(other.join().unwrap());
+    }
+}
@@ -0,0 +1,343 @@
+//! ANSI escape sequences for styled terminal output and cursor control.
+//!
+//! Colors and attributes are applied through [`Style`] and [`Styled`], which print plain text
+//! if colors are disabled: by [`set_colors_enabled`], a non-empty `NO_COLOR` variable or `TERM=dumb`.
+//! [`Control`] sequences are always printed.
+
+use core::{
+    fmt::{self, Display},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use bitflags::bitflags;
+
+use crate::internal::rt::try_runtime;
+
+pub const NO_COLOR_VAR: &str = "NO_COLOR";
+pub const TERM_VAR: &str = "TERM";
+
+/// resets all colors and attributes
+pub const RESET: &str = "\x1b[0m";
+
+const AUTO: u8 = 0;
+const ALWAYS: u8 = 1;
+const NEVER: u8 = 2;
+
+static COLORS: AtomicU8 = AtomicU8::new(AUTO);
+
+/// Overrides whether [`Styled`] values are printed with colors. None decides from the environment again.
+pub fn set_colors_enabled(enabled: Option<bool>) {
+    let mode = match enabled {
+        None => AUTO,
+        Some(true) => ALWAYS,
+        Some(false) => NEVER,
+    };
+    COLORS.store(mode, Ordering::Relaxed);
+}
+
+/// returns whether [`Styled`] values are printed with colors
+pub fn colors_enabled() -> bool {
+    match COLORS.load(Ordering::Relaxed) {
+        ALWAYS => true,
+        NEVER => false,
+        _ => {
+            let env = try_runtime().and_then(|rt| rt.env());
+            colors_enabled_for(
+                env.and_then(|env| env.get(NO_COLOR_VAR)),
+                env.and_then(|env| env.get(TERM_VAR)),
+            )
+        }
+    }
+}
+
+/// decides whether to use colors from the values of `NO_COLOR` and `TERM`
+fn colors_enabled_for(no_color: Option<&str>, term: Option<&str>) -> bool {
+    no_color.is_none_or(str::is_empty) && term != Some("dumb")
+}
+
+/// A terminal color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// an index into the 256 color palette
+    Ansi256(u8),
+    /// a 24 bit true color
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// writes the SGR parameters of the color, base is 30 for the foreground and 40 for the background
+    fn write_params(self, f: &mut fmt::Formatter<'_>, base: u8) -> fmt::Result {
+        let code = match self {
+            Self::Black => 0,
+            Self::Red => 1,
+            Self::Green => 2,
+            Self::Yellow => 3,
+            Self::Blue => 4,
+            Self::Magenta => 5,
+            Self::Cyan => 6,
+            Self::White => 7,
+            // the bright colors start at 90 and 100
+            Self::BrightBlack => 60,
+            Self::BrightRed => 61,
+            Self::BrightGreen => 62,
+            Self::BrightYellow => 63,
+            Self::BrightBlue => 64,
+            Self::BrightMagenta => 65,
+            Self::BrightCyan => 66,
+            Self::BrightWhite => 67,
+            Self::Ansi256(n) => return write!(f, "{};5;{}", base + 8, n),
+            Self::Rgb(r, g, b) => return write!(f, "{};2;{};{};{}", base + 8, r, g, b),
+        };
+        write!(f, "{}", base + code)
+    }
+}
+
+bitflags! {
+    /// Text attributes of a [`Style`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Attributes: u8 {
+        const BOLD = 1 << 0;
+        const DIM = 1 << 1;
+        const ITALIC = 1 << 2;
+        const UNDERLINE = 1 << 3;
+        const BLINK = 1 << 4;
+        const REVERSE = 1 << 5;
+        const STRIKETHROUGH = 1 << 6;
+    }
+}
+
+/// SGR parameter of every attribute, in the order of their bits
+const ATTRIBUTE_PARAMS: [u8; 7] = [1, 2, 3, 4, 5, 7, 9];
+
+/// Colors and attributes of text. Displaying a Style prints the escape sequence which sets it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    attrs: Attributes,
+}
+
+impl Style {
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            attrs: Attributes::empty(),
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    pub const fn attrs(mut self, attrs: Attributes) -> Self {
+        self.attrs = self.attrs.union(attrs);
+        self
+    }
+
+    pub const fn bold(self) -> Self {
+        self.attrs(Attributes::BOLD)
+    }
+
+    pub const fn underline(self) -> Self {
+        self.attrs(Attributes::UNDERLINE)
+    }
+
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && self.attrs.is_empty()
+    }
+
+    /// wraps value, so that it is displayed in this style
+    pub const fn paint<T: Display>(self, value: T) -> Styled<T> {
+        Styled { style: self, value }
+    }
+}
+
+impl Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_plain() {
+            return Ok(());
+        }
+        f.write_str("\x1b[")?;
+        let mut first = true;
+        let mut sep = |f: &mut fmt::Formatter<'_>| {
+            if !core::mem::take(&mut first) {
+                f.write_str(";")?;
+            }
+            Ok(())
+        };
+        for (i, param) in ATTRIBUTE_PARAMS.iter().enumerate() {
+            if self.attrs.bits() & (1 << i) != 0 {
+                sep(f)?;
+                write!(f, "{}", param)?;
+            }
+        }
+        if let Some(fg) = self.fg {
+            sep(f)?;
+            fg.write_params(f, 30)?;
+        }
+        if let Some(bg) = self.bg {
+            sep(f)?;
+            bg.write_params(f, 40)?;
+        }
+        f.write_str("m")
+    }
+}
+
+/// A value displayed in a [`Style`], followed by a reset. Only the value is displayed if colors are disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Styled<T: Display> {
+    style: Style,
+    value: T,
+}
+
+impl<T: Display> Styled<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Display> Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.style.is_plain() || !colors_enabled() {
+            return self.value.fmt(f);
+        }
+        write!(f, "{}", self.style)?;
+        // the formatter is passed on, so that width and precision apply to the value
+        self.value.fmt(f)?;
+        f.write_str(RESET)
+    }
+}
+
+/// Which part of the screen [`Control::Clear`] clears.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClearKind {
+    /// the entire screen
+    All,
+    /// from the cursor to the end of the screen
+    ToEnd,
+    /// from the start of the screen to the cursor
+    ToStart,
+    /// the line of the cursor
+    Line,
+    /// from the cursor to the end of its line
+    LineToEnd,
+    /// from the start of the line to the cursor
+    LineToStart,
+}
+
+/// A terminal control sequence, printed by displaying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Control {
+    /// moves the cursor to a 0 based row and column
+    MoveTo {
+        row: u16,
+        col: u16,
+    },
+    Up(u16),
+    Down(u16),
+    Forward(u16),
+    Back(u16),
+    HideCursor,
+    ShowCursor,
+    SaveCursor,
+    RestoreCursor,
+    Clear(ClearKind),
+    /// switches to a separate screen, e.g. for full screen programs, which keeps the contents of the main screen
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+}
+
+impl Display for Control {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::MoveTo { row, col } => write!(f, "\x1b[{};{}H", row as u32 + 1, col as u32 + 1),
+            Self::Up(n) => write!(f, "\x1b[{}A", n),
+            Self::Down(n) => write!(f, "\x1b[{}B", n),
+            Self::Forward(n) => write!(f, "\x1b[{}C", n),
+            Self::Back(n) => write!(f, "\x1b[{}D", n),
+            Self::HideCursor => f.write_str("\x1b[?25l"),
+            Self::ShowCursor => f.write_str("\x1b[?25h"),
+            Self::SaveCursor => f.write_str("\x1b7"),
+            Self::RestoreCursor => f.write_str("\x1b8"),
+            Self::Clear(kind) => f.write_str(match kind {
+                ClearKind::All => "\x1b[2J",
+                ClearKind::ToEnd => "\x1b[0J",
+                ClearKind::ToStart => "\x1b[1J",
+                ClearKind::Line => "\x1b[2K",
+                ClearKind::LineToEnd => "\x1b[0K",
+                ClearKind::LineToStart => "\x1b[1K",
+            }),
+            Self::EnterAlternateScreen => f.write_str("\x1b[?1049h"),
+            Self::LeaveAlternateScreen => f.write_str("\x1b[?1049l"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::*;
+    use crate::utils::StackBuf;
+
+    fn show(value: impl Display) -> StackBuf<64> {
+        let mut buf = StackBuf::new();
+        write!(buf, "{}", value).unwrap();
+        buf
+    }
+
+    #[test]
+    fn sequences() {
+        let style = Style::new()
+            .fg(Color::BrightCyan)
+            .bg(Color::Rgb(1, 2, 3))
+            .bold();
+        assert_eq!(show(style).as_str(), "\x1b[1;96;48;2;1;2;3m");
+        assert_eq!(
+            show(Style::new().fg(Color::Ansi256(208)).underline()).as_str(),
+            "\x1b[4;38;5;208m"
+        );
+        assert_eq!(show(Style::new().bg(Color::Red)).as_str(), "\x1b[41m");
+        assert_eq!(show(Style::new()).as_str(), "");
+        assert_eq!(
+            show(Control::MoveTo { row: 0, col: 4 }).as_str(),
+            "\x1b[1;5H"
+        );
+
+        set_colors_enabled(Some(true));
+        let styled = show(format_args!(
+            "{:>3}",
+            Style::new().fg(Color::Green).paint(7)
+        ));
+        set_colors_enabled(None);
+        assert_eq!(styled.as_str(), "\x1b[32m  7\x1b[0m");
+
+        assert!(colors_enabled_for(None, Some("xterm")));
+        assert!(colors_enabled_for(Some(""), None));
+        assert!(!colors_enabled_for(Some("1"), None));
+        assert!(!colors_enabled_for(None, Some("dumb")));
+    }
+}
@@ -13,13 +13,14 @@ use tinyos_abi::flags::{TaskWaitOptions, WaitOptions};
 
 use crate::{
     serial_println,
-    syscalls::{self, thread_exit},
+    syscalls::{self, safe, thread_exit},
 };
 
 pub type ThreadID = u64;
 pub type TaskID = u64;
 
-unsafe extern "C" fn thread_start(arg: *const ()) {
+// C-unwind, so the mock kernel can end its host thread by unwinding out of thread_exit
+unsafe extern "C-unwind" fn thread_start(arg: *const ()) {
     let closure_parts = unsafe { Box::from_raw(arg as *mut ThinFnPtr) };
     let closure_raw: *mut dyn FnOnce() =
         ptr::from_raw_parts_mut(closure_parts.data, closure_parts.vtable);
@@ -106,24 +107,22 @@ impl<T> JoinHandle<T> {
     }
 
     pub fn wait(&self, timeout: Duration) -> ThreadingResult<Option<T>> {
-        self.wait_for(timeout.as_millis() as i64)
+        self.wait_for(Some(timeout))
     }
 
     pub fn join(&self) -> ThreadingResult<T> {
-        self.wait_for(-1)
+        self.wait_for(None)
             .map(|r| r.ok_or(ThreadingErr::Fail))
             .flatten()
     }
 
-    fn wait_for(&self, timeout: i64) -> ThreadingResult<Option<T>> {
-        _ = unsafe {
-            syscalls::thread_join(
-                self.id,
-                timeout,
-                WaitOptions::empty(),
-                TaskWaitOptions::W_EXIT,
-            )
-        }
+    fn wait_for(&self, timeout: Option<Duration>) -> ThreadingResult<Option<T>> {
+        _ = safe::thread_join(
+            self.id,
+            timeout,
+            WaitOptions::empty(),
+            TaskWaitOptions::W_EXIT,
+        )
         .map_err(|_| ThreadingErr::Fail)?;
         self.inner.receive().map(|r| Some(r))
     }
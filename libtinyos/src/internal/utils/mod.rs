@@ -1 +1,74 @@
+use core::fmt::{self, Write};
 
+/// A fixed size buffer implementing [`fmt::Write`], which silently truncates once full.
+/// Used to format messages without allocating.
+pub struct StackBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<const N: usize> StackBuf<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only whole chars are ever written, see write_str
+        unsafe { str::from_utf8_unchecked(self.as_bytes()) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// returns true if some output did not fit into the buffer
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+}
+
+impl<const N: usize> Default for StackBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Write for StackBuf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let free = N - self.len;
+        let mut n = s.len().min(free);
+        if n < s.len() {
+            self.truncated = true;
+            while !s.is_char_boundary(n) {
+                n -= 1;
+            }
+        }
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for StackBuf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StackBuf").field(&self.as_str()).finish()
+    }
+}
@@ -1,9 +1,12 @@
 #![no_std]
 #![allow(unused_imports)]
 #![feature(unsafe_cell_access, ptr_metadata)]
+#![cfg_attr(not(feature = "mock-kernel"), feature(linkage))]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "mock-kernel")]
+extern crate std;
 
 /// cbindgen:ignore
 pub(crate) mod arch;
@@ -17,15 +20,13 @@ pub mod syscalls;
 pub use crate::internal::alloc as tiny_alloc;
 #[cfg(feature = "alloc")]
 pub use crate::internal::thread;
-pub use crate::internal::{collections, fs, io, os, path, process, sync, time, utils};
+pub use crate::internal::{
+    backtrace, collections, fs, io, log, os, panic, path, process, sync, term, time, utils,
+};
 pub use c_api::*;
 
+#[cfg(not(feature = "mock-kernel"))]
 #[panic_handler]
-fn lib_panic(_info: &core::panic::PanicInfo) -> ! {
-    #[cfg(feature = "alloc")]
-    {
-        eprintln!("{}", _info);
-        eprintln!("exiting...");
-    }
-    unsafe { syscalls::exit(2) }
+fn lib_panic(info: &core::panic::PanicInfo) -> ! {
+    panic::handle(info)
 }
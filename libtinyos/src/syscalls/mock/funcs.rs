@@ -0,0 +1,200 @@
+use core::{slice, time::Duration};
+
+use tinyos_abi::{
+    flags::{TaskStateChange, TaskWaitOptions, WaitOptions},
+    types::{FDAction, SysErrCode, SysResult},
+};
+
+use super::{DEFAULT_PIPE_CAP, PID, kernel};
+use crate::syscalls::{FileDescriptor, OpenOptions, PageTableFlags};
+
+unsafe fn str_from_raw<'a>(ptr: *const u8, len: usize) -> SysResult<&'a str> {
+    if ptr.is_null() {
+        return Err(SysErrCode::InvalidArgument);
+    }
+    let bytes = unsafe { slice::from_raw_parts(ptr, len) };
+    str::from_utf8(bytes).map_err(|_| SysErrCode::InvalidArgument)
+}
+
+fn read_timeout(timeout: usize) -> Option<Duration> {
+    (timeout != 0).then(|| Duration::from_millis(timeout as u64))
+}
+
+fn wait_timeout(timeout: i64) -> Option<Duration> {
+    (timeout >= 0).then(|| Duration::from_millis(timeout as u64))
+}
+
+pub unsafe fn exit(status: i64) -> ! {
+    std::process::exit(status as i32)
+}
+
+pub unsafe fn kill(pid: u64, status: i64) -> SysResult<()> {
+    if pid == PID {
+        unsafe { exit(status) }
+    }
+    Err(SysErrCode::NotFound)
+}
+
+pub unsafe fn open(path: *const u8, len: usize, flags: OpenOptions) -> SysResult<FileDescriptor> {
+    let path = unsafe { str_from_raw(path, len) }?;
+    kernel().lock().open(path, flags)
+}
+
+pub unsafe fn close(fd: FileDescriptor) -> SysResult<()> {
+    let kernel = kernel();
+    let r = kernel.lock().close(fd);
+    // closing the last end of a pipe wakes up its peers
+    kernel.changed.notify_all();
+    r
+}
+
+pub unsafe fn seek(fd: FileDescriptor, offset: usize) -> SysResult<()> {
+    kernel().lock().seek(fd, offset)
+}
+
+pub unsafe fn dup(old: FileDescriptor, new: Option<FileDescriptor>) -> SysResult<FileDescriptor> {
+    let kernel = kernel();
+    let r = kernel.lock().dup(old, new);
+    kernel.changed.notify_all();
+    r
+}
+
+pub unsafe fn write(fd: FileDescriptor, buf: *const u8, len: usize) -> SysResult<isize> {
+    let buf = unsafe { slice::from_raw_parts(buf, len) };
+    kernel()
+        .wait_for(None, |state| state.try_write(fd, buf))
+        .map(|n| n as isize)
+}
+
+pub unsafe fn read(
+    fd: FileDescriptor,
+    buf: *mut u8,
+    len: usize,
+    timeout: usize,
+) -> SysResult<isize> {
+    let buf = unsafe { slice::from_raw_parts_mut(buf, len) };
+    kernel()
+        .wait_for(read_timeout(timeout), |state| state.try_read(fd, buf))
+        .map(|n| n as isize)
+}
+
+pub unsafe fn yield_now() {
+    std::thread::yield_now();
+}
+
+pub unsafe fn mmap(
+    len: usize,
+    _ptr: *mut u8,
+    _flags: PageTableFlags,
+    fd: Option<FileDescriptor>,
+) -> SysResult<*mut u8> {
+    // the address hint is ignored, as the host decides where memory lives
+    kernel().lock().mmap(len, fd)
+}
+
+pub unsafe fn munmap(ptr: *mut u8, _len: usize) {
+    kernel().lock().munmap(ptr);
+}
+
+pub unsafe fn fork() -> SysResult<bool> {
+    Err(SysErrCode::InvalidArgument)
+}
+
+pub unsafe fn get_pid() -> SysResult<u64> {
+    Ok(PID)
+}
+
+pub unsafe fn spawn(_elf: *const u8, _len: usize) -> SysResult<()> {
+    Err(SysErrCode::InvalidArgument)
+}
+
+pub unsafe fn dbg(buf: *const u8, len: usize) -> SysResult<()> {
+    let buf = unsafe { slice::from_raw_parts(buf, len) };
+    kernel().lock().serial.extend_from_slice(buf);
+    Ok(())
+}
+
+pub unsafe fn execve(
+    _path: *const u8,
+    _len: usize,
+    _argc: usize,
+    _argv: *const u8,
+    _envc: usize,
+    _envp: *const u8,
+) -> SysResult<u64> {
+    Err(SysErrCode::InvalidArgument)
+}
+
+pub unsafe fn thread_create(start_routine: *const (), args: *const ()) -> SysResult<u64> {
+    super::thread_create(start_routine, args)
+}
+
+pub unsafe fn thread_exit() -> ! {
+    super::thread_exit()
+}
+
+pub unsafe fn thread_cancel(_tid: u64) -> SysResult<i64> {
+    // host threads cannot be cancelled
+    Err(SysErrCode::InvalidArgument)
+}
+
+pub unsafe fn thread_join(
+    tid: u64,
+    timeout: i64,
+    _w_flags: WaitOptions,
+    _tw_flags: TaskWaitOptions,
+) -> SysResult<TaskStateChange> {
+    super::thread_join(tid, wait_timeout(timeout))
+}
+
+pub unsafe fn wait_pid(
+    _pid: u64,
+    _timeout: i64,
+    _w_flags: WaitOptions,
+    _tw_flags: TaskWaitOptions,
+) -> SysResult<TaskStateChange> {
+    Err(SysErrCode::NotFound)
+}
+
+pub unsafe fn eventfd() -> SysResult<u64> {
+    Ok(kernel().lock().eventfd() as u64)
+}
+
+pub unsafe fn waittime(time: u64) -> SysResult<()> {
+    kernel().lock().clock += Duration::from_millis(time);
+    std::thread::yield_now();
+    Ok(())
+}
+
+pub unsafe fn time() -> SysResult<u64> {
+    Ok(kernel().lock().clock.as_millis() as u64)
+}
+
+pub unsafe fn get_tid() -> u64 {
+    super::CURRENT_TID.get()
+}
+
+pub unsafe fn get_pgrid() -> u64 {
+    PID
+}
+
+pub unsafe fn pipe(fds: *mut [u32; 2], cap: isize) -> SysResult<()> {
+    let cap = usize::try_from(cap).unwrap_or(DEFAULT_PIPE_CAP).max(1);
+    let pipe = kernel().lock().pipe(cap);
+    unsafe { fds.write(pipe) };
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn spawn_process(
+    _path: *const u8,
+    _len: usize,
+    _argc: usize,
+    _argv: *const u8,
+    _envc: usize,
+    _envp: *const u8,
+    _fd_actions: *const FDAction,
+    _fd_action_count: usize,
+) -> SysResult<u64> {
+    Err(SysErrCode::InvalidArgument)
+}
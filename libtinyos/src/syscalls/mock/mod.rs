@@ -0,0 +1,820 @@
+//! An in-process simulation of the tinyOS kernel.
+//!
+//! With the `mock-kernel` feature, every syscall in [`crate::syscalls`] is served by this module instead of `int 0x80`,
+//! which allows testing libtinyos (and crates built on it) with `cargo test` on a regular host.
+//! The simulated kernel provides
+//! - a ramfs, prepopulated with the gfx config and a fake `/proc/kernel/gfx/fb`
+//! - pipes and eventfds
+//! - captured stdin/stdout/stderr and serial output
+//! - threads backed by host threads
+//! - a fake clock, which only advances through `waittime` or [`advance_clock`]
+//!
+//! All processes share the same kernel, so tests should use distinct paths,
+//! and hold a [`capture`] guard while they use the captured stdin, stdout, stderr or serial output.
+
+use std::{
+    alloc::{self, Layout},
+    boxed::Box,
+    cell::Cell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    format,
+    string::{String, ToString},
+    sync::{Condvar, Mutex, MutexGuard, OnceLock},
+    thread, thread_local,
+    time::{Duration, Instant},
+    vec::Vec,
+};
+
+use tinyos_abi::{flags::TaskStateChange, types::SysErrCode};
+
+use crate::{
+    internal::rt,
+    syscalls::{FileDescriptor, OpenOptions, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO},
+};
+
+pub(crate) mod funcs;
+
+pub const GFX_CONFIG_PATH: &str = "/ram/.devconf/gfx/config.conf";
+pub const FB_PATH: &str = "/proc/kernel/gfx/fb";
+pub const FB_WIDTH: u32 = 640;
+pub const FB_HEIGHT: u32 = 480;
+pub const FB_BPP: u16 = 32;
+pub const FB_PITCH: u32 = FB_WIDTH * (FB_BPP as u32 / 8);
+
+const PID: u64 = 1;
+const MAIN_TID: u64 = 1;
+const PAGE_SIZE: usize = 4096;
+const DEFAULT_PIPE_CAP: usize = 4096;
+const EVENTFD_SIZE: usize = size_of::<u64>();
+
+type KResult<T> = Result<T, SysErrCode>;
+
+thread_local! {
+    static CURRENT_TID: Cell<u64> = const { Cell::new(MAIN_TID) };
+}
+
+/// Initializes the runtime data returned by [`crate::os::args`] and [`crate::os::env`].
+/// args is ' ' separated, env '\0' separated, as handed to `_start` by the kernel.
+/// Only the first call has an effect.
+pub fn boot(args: &'static str, env: &'static str) {
+    rt::init(args.len(), args.as_ptr(), env.len(), env.as_ptr());
+}
+
+/// creates or replaces the file at path, creating missing parent directories
+pub fn write_file(path: &str, data: &[u8]) {
+    let mut state = kernel().lock();
+    state.mkdir_all(parent(path));
+    state
+        .nodes
+        .insert(path.to_string(), Node::File(data.to_vec()));
+}
+
+pub fn read_file(path: &str) -> Option<Vec<u8>> {
+    match kernel().lock().nodes.get(path)? {
+        Node::File(data) => Some(data.clone()),
+        _ => None,
+    }
+}
+
+pub fn create_dir(path: &str) {
+    kernel().lock().mkdir_all(path);
+}
+
+/// Serialises the tests which use captured output, see [`capture`].
+#[must_use]
+pub struct CaptureGuard {
+    _guard: MutexGuard<'static, ()>,
+}
+
+/// Waits until no other test holds a [`CaptureGuard`], then clears stdin, stdout, stderr and the serial output.
+/// Tests which do not capture may still write to them, so the output should only be searched for the tests own output.
+pub fn capture() -> CaptureGuard {
+    static CAPTURE: Mutex<()> = Mutex::new(());
+    let guard = CAPTURE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = kernel().lock();
+    state.stdin.clear();
+    state.stdin_closed = false;
+    state.stdout.clear();
+    state.stderr.clear();
+    state.serial.clear();
+    CaptureGuard { _guard: guard }
+}
+
+pub fn push_stdin(data: &[u8]) {
+    let kernel = kernel();
+    kernel.lock().stdin.extend(data);
+    kernel.changed.notify_all();
+}
+
+/// marks stdin as closed, reads return EOF once the queued data is consumed
+pub fn close_stdin() {
+    let kernel = kernel();
+    kernel.lock().stdin_closed = true;
+    kernel.changed.notify_all();
+}
+
+pub fn take_stdout() -> Vec<u8> {
+    core::mem::take(&mut kernel().lock().stdout)
+}
+
+pub fn take_stderr() -> Vec<u8> {
+    core::mem::take(&mut kernel().lock().stderr)
+}
+
+/// returns everything written through the `dbg` syscall so far
+pub fn take_serial() -> Vec<u8> {
+    core::mem::take(&mut kernel().lock().serial)
+}
+
+pub fn advance_clock(dur: Duration) {
+    kernel().lock().clock += dur;
+}
+
+/// returns a copy of the fake framebuffer
+pub fn framebuffer() -> Vec<u8> {
+    let state = kernel().lock();
+    state.fb.as_slice().to_vec()
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map(|(p, _)| p).unwrap_or("")
+}
+
+fn kernel() -> &'static Kernel {
+    static KERNEL: OnceLock<Kernel> = OnceLock::new();
+    KERNEL.get_or_init(Kernel::new)
+}
+
+struct Kernel {
+    state: Mutex<State>,
+    /// notified whenever a pipe, eventfd, stdin or thread changes state
+    changed: Condvar,
+}
+
+impl Kernel {
+    fn new() -> Self {
+        let mut state = State {
+            nodes: BTreeMap::new(),
+            fds: BTreeMap::new(),
+            files: HashMap::new(),
+            next_file: 0,
+            pipes: HashMap::new(),
+            events: HashMap::new(),
+            next_object: 0,
+            threads: HashMap::new(),
+            next_tid: MAIN_TID + 1,
+            mappings: HashMap::new(),
+            fb: FrameBuffer::new((FB_PITCH * FB_HEIGHT) as usize),
+            stdin: VecDeque::new(),
+            stdin_closed: false,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            serial: Vec::new(),
+            clock: Duration::ZERO,
+        };
+
+        state.mkdir_all("/ram");
+        state.mkdir_all(parent(GFX_CONFIG_PATH));
+        state.mkdir_all(parent(FB_PATH));
+        let config = format!(
+            "16 8 8 8 0 8 {} {} {} {}",
+            FB_BPP, FB_WIDTH, FB_HEIGHT, FB_PITCH
+        );
+        state
+            .nodes
+            .insert(GFX_CONFIG_PATH.into(), Node::File(config.into_bytes()));
+        state.nodes.insert(FB_PATH.into(), Node::FrameBuffer);
+
+        for (fd, kind) in [
+            (STDIN_FILENO, FileKind::Stdin),
+            (STDOUT_FILENO, FileKind::Stdout),
+            (STDERR_FILENO, FileKind::Stderr),
+        ] {
+            let file = state.insert_file(OpenFile {
+                kind,
+                flags: OpenOptions::READ | OpenOptions::WRITE,
+                refs: 0,
+                path: None,
+            });
+            state.attach(fd, file);
+        }
+
+        Self {
+            state: Mutex::new(state),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// blocks until f returns Some, or until timeout has passed
+    fn wait_for<T>(
+        &self,
+        timeout: Option<Duration>,
+        mut f: impl FnMut(&mut State) -> Option<KResult<T>>,
+    ) -> KResult<T> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let mut state = self.lock();
+        loop {
+            if let Some(r) = f(&mut state) {
+                self.changed.notify_all();
+                return r;
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(SysErrCode::TimedOut);
+                    }
+                    self.changed
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+                None => self.changed.wait(state).unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+    }
+}
+
+enum Node {
+    Dir,
+    File(Vec<u8>),
+    FrameBuffer,
+}
+
+#[derive(Clone, Copy)]
+enum FileKind {
+    Stdin,
+    Stdout,
+    Stderr,
+    /// a file in the ramfs, at the offset pos
+    Node {
+        pos: usize,
+    },
+    PipeReader(usize),
+    PipeWriter(usize),
+    Event(usize),
+}
+
+/// an open file description, which may be shared by several fds
+struct OpenFile {
+    kind: FileKind,
+    flags: OpenOptions,
+    refs: usize,
+    /// path of the ramfs node, if kind is FileKind::Node
+    path: Option<String>,
+}
+
+struct Pipe {
+    buf: VecDeque<u8>,
+    cap: usize,
+    readers: usize,
+    writers: usize,
+}
+
+struct Thread {
+    exited: bool,
+}
+
+struct FrameBuffer {
+    addr: usize,
+    len: usize,
+}
+
+impl FrameBuffer {
+    fn new(len: usize) -> Self {
+        let layout = Layout::from_size_align(len, PAGE_SIZE).unwrap();
+        // the framebuffer lives for the entire process, as it may be mapped by any number of users
+        let addr = unsafe { alloc::alloc_zeroed(layout) } as usize;
+        assert_ne!(addr, 0);
+        Self { addr, len }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.addr as *const u8, self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.addr as *mut u8, self.len) }
+    }
+}
+
+struct State {
+    nodes: BTreeMap<String, Node>,
+    fds: BTreeMap<FileDescriptor, usize>,
+    files: HashMap<usize, OpenFile>,
+    next_file: usize,
+    pipes: HashMap<usize, Pipe>,
+    events: HashMap<usize, u64>,
+    next_object: usize,
+    threads: HashMap<u64, Thread>,
+    next_tid: u64,
+    /// addr -> layout of anonymous mappings
+    mappings: HashMap<usize, Layout>,
+    fb: FrameBuffer,
+    stdin: VecDeque<u8>,
+    stdin_closed: bool,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    serial: Vec<u8>,
+    clock: Duration,
+}
+
+impl State {
+    fn mkdir_all(&mut self, path: &str) {
+        let mut current = String::new();
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            current.push('/');
+            current.push_str(segment);
+            self.nodes.entry(current.clone()).or_insert(Node::Dir);
+        }
+    }
+
+    fn next_object(&mut self) -> usize {
+        self.next_object += 1;
+        self.next_object
+    }
+
+    fn insert_file(&mut self, file: OpenFile) -> usize {
+        self.next_file += 1;
+        self.files.insert(self.next_file, file);
+        self.next_file
+    }
+
+    fn free_fd(&self) -> FileDescriptor {
+        (0..)
+            .find(|fd| !self.fds.contains_key(fd))
+            .expect("out of file descriptors")
+    }
+
+    fn attach(&mut self, fd: FileDescriptor, file: usize) {
+        self.files.get_mut(&file).unwrap().refs += 1;
+        if let Some(old) = self.fds.insert(fd, file) {
+            self.release(old);
+        }
+    }
+
+    fn install(&mut self, file: OpenFile) -> FileDescriptor {
+        let file = self.insert_file(file);
+        let fd = self.free_fd();
+        self.attach(fd, file);
+        fd
+    }
+
+    fn release(&mut self, file: usize) {
+        let entry = self.files.get_mut(&file).unwrap();
+        entry.refs -= 1;
+        if entry.refs > 0 {
+            return;
+        }
+        let file = self.files.remove(&file).unwrap();
+        match file.kind {
+            FileKind::PipeReader(id) => self.pipes.get_mut(&id).unwrap().readers -= 1,
+            FileKind::PipeWriter(id) => self.pipes.get_mut(&id).unwrap().writers -= 1,
+            // an eventfd is only ever referenced by a single (possibly shared) description
+            FileKind::Event(id) => {
+                self.events.remove(&id);
+            }
+            _ => {}
+        }
+        if let FileKind::PipeReader(id) | FileKind::PipeWriter(id) = file.kind {
+            let pipe = &self.pipes[&id];
+            if pipe.readers == 0 && pipe.writers == 0 {
+                self.pipes.remove(&id);
+            }
+        }
+    }
+
+    fn file(&self, fd: FileDescriptor) -> KResult<usize> {
+        self.fds
+            .get(&fd)
+            .copied()
+            .ok_or(SysErrCode::InvalidArgument)
+    }
+
+    fn open_file(&mut self, fd: FileDescriptor) -> KResult<&mut OpenFile> {
+        let file = self.file(fd)?;
+        Ok(self.files.get_mut(&file).unwrap())
+    }
+
+    /// returns a copy of the kind, flags and node path of the file description behind fd
+    fn describe(&mut self, fd: FileDescriptor) -> KResult<(FileKind, OpenOptions, String)> {
+        let file = self.open_file(fd)?;
+        Ok((file.kind, file.flags, file.path.clone().unwrap_or_default()))
+    }
+
+    fn set_pos(&mut self, fd: FileDescriptor, new: usize) {
+        if let Ok(OpenFile {
+            kind: FileKind::Node { pos },
+            ..
+        }) = self.open_file(fd)
+        {
+            *pos = new;
+        }
+    }
+
+    fn open(&mut self, path: &str, flags: OpenOptions) -> KResult<FileDescriptor> {
+        match self.nodes.get_mut(path) {
+            Some(Node::File(data)) => {
+                if flags.contains(OpenOptions::TRUNCATE) {
+                    data.clear();
+                }
+            }
+            Some(_) => {}
+            None => {
+                if !flags.contains(OpenOptions::CREATE) {
+                    return Err(SysErrCode::NotFound);
+                }
+                if !matches!(self.nodes.get(parent(path)), Some(Node::Dir)) {
+                    return Err(SysErrCode::NotFound);
+                }
+                self.nodes.insert(path.into(), Node::File(Vec::new()));
+            }
+        }
+        Ok(self.install(OpenFile {
+            kind: FileKind::Node { pos: 0 },
+            flags,
+            refs: 0,
+            path: Some(path.into()),
+        }))
+    }
+
+    fn seek(&mut self, fd: FileDescriptor, offset: usize) -> KResult<()> {
+        match self.open_file(fd)?.kind {
+            FileKind::Node { ref mut pos } => {
+                *pos = offset;
+                Ok(())
+            }
+            _ => Err(SysErrCode::InvalidArgument),
+        }
+    }
+
+    fn dup(&mut self, old: FileDescriptor, new: Option<FileDescriptor>) -> KResult<FileDescriptor> {
+        let file = self.file(old)?;
+        let new = new.unwrap_or_else(|| self.free_fd());
+        if new != old {
+            self.attach(new, file);
+        }
+        Ok(new)
+    }
+
+    fn close(&mut self, fd: FileDescriptor) -> KResult<()> {
+        let file = self.fds.remove(&fd).ok_or(SysErrCode::InvalidArgument)?;
+        self.release(file);
+        Ok(())
+    }
+
+    fn pipe(&mut self, cap: usize) -> [FileDescriptor; 2] {
+        let id = self.next_object();
+        self.pipes.insert(
+            id,
+            Pipe {
+                buf: VecDeque::new(),
+                cap,
+                readers: 1,
+                writers: 1,
+            },
+        );
+        let reader = self.install(OpenFile {
+            kind: FileKind::PipeReader(id),
+            flags: OpenOptions::READ,
+            refs: 0,
+            path: None,
+        });
+        let writer = self.install(OpenFile {
+            kind: FileKind::PipeWriter(id),
+            flags: OpenOptions::WRITE,
+            refs: 0,
+            path: None,
+        });
+        [reader, writer]
+    }
+
+    fn eventfd(&mut self) -> FileDescriptor {
+        let id = self.next_object();
+        self.events.insert(id, 0);
+        self.install(OpenFile {
+            kind: FileKind::Event(id),
+            flags: OpenOptions::READ | OpenOptions::WRITE,
+            refs: 0,
+            path: None,
+        })
+    }
+
+    /// Tries to read into buf. Returns None if the read would block.
+    fn try_read(&mut self, fd: FileDescriptor, buf: &mut [u8]) -> Option<KResult<usize>> {
+        let (kind, flags, path) = match self.describe(fd) {
+            Ok(f) => f,
+            Err(e) => return Some(Err(e)),
+        };
+        if !flags.contains(OpenOptions::READ) {
+            return Some(Err(SysErrCode::PermissionDenied));
+        }
+        match kind {
+            FileKind::Stdin => {
+                if self.stdin.is_empty() && !self.stdin_closed {
+                    return None;
+                }
+                let n = buf.len().min(self.stdin.len());
+                for (dst, src) in buf.iter_mut().zip(self.stdin.drain(..n)) {
+                    *dst = src;
+                }
+                Some(Ok(n))
+            }
+            FileKind::Stdout | FileKind::Stderr => Some(Err(SysErrCode::PermissionDenied)),
+            FileKind::Node { pos } => {
+                let data = match self.nodes.get(&path) {
+                    Some(Node::File(data)) => data.as_slice(),
+                    Some(Node::FrameBuffer) => self.fb.as_slice(),
+                    _ => return Some(Err(SysErrCode::InvalidArgument)),
+                };
+                let start = pos.min(data.len());
+                let n = buf.len().min(data.len() - start);
+                buf[..n].copy_from_slice(&data[start..start + n]);
+                self.set_pos(fd, start + n);
+                Some(Ok(n))
+            }
+            FileKind::PipeReader(id) => {
+                let pipe = self.pipes.get_mut(&id).unwrap();
+                if pipe.buf.is_empty() {
+                    // EOF once every writer is gone
+                    return if pipe.writers == 0 { Some(Ok(0)) } else { None };
+                }
+                let n = buf.len().min(pipe.buf.len());
+                for (dst, src) in buf.iter_mut().zip(pipe.buf.drain(..n)) {
+                    *dst = src;
+                }
+                Some(Ok(n))
+            }
+            FileKind::PipeWriter(_) => Some(Err(SysErrCode::PermissionDenied)),
+            FileKind::Event(id) => {
+                if buf.len() < EVENTFD_SIZE {
+                    return Some(Err(SysErrCode::InvalidArgument));
+                }
+                let count = self.events.get_mut(&id).unwrap();
+                if *count == 0 {
+                    return None;
+                }
+                buf[..EVENTFD_SIZE].copy_from_slice(&count.to_ne_bytes());
+                *count = 0;
+                Some(Ok(EVENTFD_SIZE))
+            }
+        }
+    }
+
+    /// Tries to write buf. Returns None if the write would block.
+    fn try_write(&mut self, fd: FileDescriptor, buf: &[u8]) -> Option<KResult<usize>> {
+        let (kind, flags, path) = match self.describe(fd) {
+            Ok(f) => f,
+            Err(e) => return Some(Err(e)),
+        };
+        if !flags.contains(OpenOptions::WRITE) {
+            return Some(Err(SysErrCode::PermissionDenied));
+        }
+        match kind {
+            FileKind::Stdin => Some(Err(SysErrCode::PermissionDenied)),
+            FileKind::Stdout => {
+                self.stdout.extend_from_slice(buf);
+                Some(Ok(buf.len()))
+            }
+            FileKind::Stderr => {
+                self.stderr.extend_from_slice(buf);
+                Some(Ok(buf.len()))
+            }
+            FileKind::Node { pos } => {
+                let data = match self.nodes.get_mut(&path) {
+                    Some(Node::File(data)) => data,
+                    Some(Node::FrameBuffer) => {
+                        let fb = self.fb.as_mut_slice();
+                        let start = pos.min(fb.len());
+                        let n = buf.len().min(fb.len() - start);
+                        fb[start..start + n].copy_from_slice(&buf[..n]);
+                        self.set_pos(fd, start + n);
+                        return Some(Ok(n));
+                    }
+                    _ => return Some(Err(SysErrCode::InvalidArgument)),
+                };
+                let start = if flags.contains(OpenOptions::APPEND) {
+                    data.len()
+                } else {
+                    pos
+                };
+                let end = start + buf.len();
+                if data.len() < end {
+                    data.resize(end, 0);
+                }
+                data[start..end].copy_from_slice(buf);
+                self.set_pos(fd, end);
+                Some(Ok(buf.len()))
+            }
+            FileKind::PipeReader(_) => Some(Err(SysErrCode::PermissionDenied)),
+            FileKind::PipeWriter(id) => {
+                let pipe = self.pipes.get_mut(&id).unwrap();
+                if pipe.readers == 0 {
+                    return Some(Err(SysErrCode::InvalidArgument));
+                }
+                let n = buf.len().min(pipe.cap - pipe.buf.len());
+                if n == 0 && !buf.is_empty() {
+                    return None;
+                }
+                pipe.buf.extend(&buf[..n]);
+                Some(Ok(n))
+            }
+            FileKind::Event(id) => {
+                let Some(bytes) = buf.get(..EVENTFD_SIZE) else {
+                    return Some(Err(SysErrCode::InvalidArgument));
+                };
+                let add = u64::from_ne_bytes(bytes.try_into().unwrap());
+                let count = self.events.get_mut(&id).unwrap();
+                *count = count.saturating_add(add);
+                Some(Ok(EVENTFD_SIZE))
+            }
+        }
+    }
+
+    fn mmap(&mut self, len: usize, fd: Option<FileDescriptor>) -> KResult<*mut u8> {
+        let contents = match fd {
+            None => None,
+            Some(fd) => {
+                let (FileKind::Node { pos }, _, path) = self.describe(fd)? else {
+                    return Err(SysErrCode::InvalidArgument);
+                };
+                match self.nodes.get(&path) {
+                    // the framebuffer is shared by every mapping of it
+                    Some(Node::FrameBuffer) => {
+                        if pos + len > self.fb.len {
+                            return Err(SysErrCode::InvalidArgument);
+                        }
+                        return Ok((self.fb.addr + pos) as *mut u8);
+                    }
+                    Some(Node::File(data)) => Some(data[pos.min(data.len())..].to_vec()),
+                    _ => return Err(SysErrCode::InvalidArgument),
+                }
+            }
+        };
+        let layout = Layout::from_size_align(len.max(1).next_multiple_of(PAGE_SIZE), PAGE_SIZE)
+            .map_err(|_| SysErrCode::InvalidArgument)?;
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return Err(SysErrCode::OutOfMemory);
+        }
+        if let Some(contents) = contents {
+            let n = contents.len().min(len);
+            unsafe { ptr.copy_from_nonoverlapping(contents.as_ptr(), n) };
+        }
+        self.mappings.insert(ptr as usize, layout);
+        Ok(ptr)
+    }
+
+    fn munmap(&mut self, ptr: *mut u8) {
+        if let Some(layout) = self.mappings.remove(&(ptr as usize)) {
+            unsafe { alloc::dealloc(ptr, layout) };
+        }
+    }
+}
+
+fn thread_create(start_routine: *const (), args: *const ()) -> KResult<u64> {
+    let kernel = kernel();
+    let tid = {
+        let mut state = kernel.lock();
+        let tid = state.next_tid;
+        state.next_tid += 1;
+        state.threads.insert(tid, Thread { exited: false });
+        tid
+    };
+    let (start_routine, args) = (start_routine as usize, args as usize);
+    thread::Builder::new()
+        .name(format!("tinyos-{}", tid))
+        .spawn(move || {
+            CURRENT_TID.set(tid);
+            let start: unsafe extern "C-unwind" fn(*const ()) =
+                unsafe { core::mem::transmute(start_routine as *const ()) };
+            let res = std::panic::catch_unwind(|| unsafe { start(args as *const ()) });
+            mark_exited(tid);
+            // thread_exit unwinds to here, to end the host thread
+            if let Err(payload) = res
+                && !payload.is::<ThreadExit>()
+            {
+                std::panic::resume_unwind(payload);
+            }
+        })
+        .map_err(|_| SysErrCode::OutOfMemory)?;
+    Ok(tid)
+}
+
+/// the unwind payload of [`thread_exit`]
+struct ThreadExit;
+
+fn thread_exit() -> ! {
+    // resume_unwind skips the panic hook, the payload is caught in thread_create
+    std::panic::resume_unwind(Box::new(ThreadExit))
+}
+
+fn mark_exited(tid: u64) {
+    let kernel = kernel();
+    if let Some(thread) = kernel.lock().threads.get_mut(&tid) {
+        thread.exited = true;
+    }
+    kernel.changed.notify_all();
+}
+
+fn thread_join(tid: u64, timeout: Option<Duration>) -> KResult<TaskStateChange> {
+    kernel().wait_for(timeout, |state| match state.threads.get(&tid) {
+        None => Some(Err(SysErrCode::NotFound)),
+        Some(thread) if thread.exited => Some(Ok(TaskStateChange::EXIT)),
+        Some(_) => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syscalls::safe;
+
+    #[test]
+    fn ramfs_roundtrip() {
+        let path = "/ram/mock/roundtrip.txt";
+        create_dir("/ram/mock");
+        let fd = safe::open(path, OpenOptions::WRITE | OpenOptions::CREATE).unwrap();
+        assert_eq!(safe::write(fd, b"hello world"), Ok(11));
+        safe::close(fd).unwrap();
+        assert_eq!(read_file(path).unwrap(), b"hello world");
+
+        let fd = safe::open(path, OpenOptions::READ).unwrap();
+        safe::seek(fd, 6).unwrap();
+        let mut buf = [0; 16];
+        assert_eq!(safe::read(fd, &mut buf, None), Ok(5));
+        assert_eq!(&buf[..5], b"world");
+        assert_eq!(safe::read(fd, &mut buf, None), Ok(0));
+        safe::close(fd).unwrap();
+
+        assert_eq!(
+            safe::open("/ram/mock/missing", OpenOptions::READ),
+            Err(SysErrCode::NotFound)
+        );
+    }
+
+    #[test]
+    fn pipe_eof_after_writer_closed() {
+        let [reader, writer] = safe::pipe(Some(4)).unwrap();
+        assert_eq!(safe::write(writer, b"abcdef"), Ok(4));
+        let mut buf = [0; 8];
+        assert_eq!(safe::read(reader, &mut buf, None), Ok(4));
+        assert_eq!(
+            safe::read(reader, &mut buf, Some(Duration::from_millis(1))),
+            Err(SysErrCode::TimedOut)
+        );
+        safe::close(writer).unwrap();
+        assert_eq!(safe::read(reader, &mut buf, None), Ok(0));
+        safe::close(reader).unwrap();
+    }
+
+    #[test]
+    fn eventfd_accumulates() {
+        let fd = safe::eventfd().unwrap();
+        safe::write(fd, &2u64.to_ne_bytes()).unwrap();
+        safe::write(fd, &3u64.to_ne_bytes()).unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(safe::read(fd, &mut buf, None), Ok(8));
+        assert_eq!(u64::from_ne_bytes(buf), 5);
+        safe::close(fd).unwrap();
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn threads_join() {
+        use core::sync::atomic::{AtomicBool, Ordering};
+
+        static ENDED: AtomicBool = AtomicBool::new(false);
+        struct OnEnd;
+        impl Drop for OnEnd {
+            fn drop(&mut self) {
+                ENDED.store(true, Ordering::Relaxed);
+            }
+        }
+        thread_local! {
+            static ON_END: OnEnd = const { OnEnd };
+        }
+
+        let handle = crate::thread::spawn(|| ON_END.with(|_| 21 * 2)).unwrap();
+        assert_eq!(handle.join(), Ok(42));
+        // the host thread ends, and runs its thread local destructors, after the join is signalled
+        for _ in 0..1000 {
+            if ENDED.load(Ordering::Relaxed) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("the host thread did not end after thread_exit");
+    }
+
+    #[test]
+    fn fake_clock() {
+        let before = safe::time().unwrap();
+        safe::waittime(Duration::from_millis(50)).unwrap();
+        assert!(safe::time().unwrap() >= before + Duration::from_millis(50));
+    }
+}
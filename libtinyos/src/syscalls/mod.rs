@@ -1,9 +1,37 @@
 use bitflags::bitflags;
 
-mod funcs;
-pub use funcs::*;
+pub mod safe;
 pub use tinyos_abi::{consts::*, flags::*, types::*};
 
+cfg_if::cfg_if! {
+    if #[cfg(feature = "mock-kernel")] {
+        pub mod mock;
+        use mock::funcs as backend;
+    } else {
+        mod funcs;
+        use funcs as backend;
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "trace-syscalls")] {
+        pub mod trace;
+        pub use trace::funcs::*;
+    } else {
+        pub use backend::*;
+    }
+}
+
+/// raw syscalls cannot be served by the mock kernel, use the functions in [`syscalls`](crate::syscalls) instead
+#[cfg(feature = "mock-kernel")]
+#[macro_export]
+macro_rules! syscall {
+    ($($arg:expr),+ $(,)?) => {
+        compile_error!("raw syscalls are not supported by the mock kernel, use the functions in libtinyos::syscalls")
+    };
+}
+
+#[cfg(not(feature = "mock-kernel"))]
 #[macro_export]
 macro_rules! syscall {
     ($rax:expr) => {{
@@ -0,0 +1,254 @@
+//! Safe wrappers around the raw syscalls in [`crate::syscalls`].
+//!
+//! All functions take slices, strings and [`Path`]s instead of pointer/length pairs,
+//! so the lengths handed to the kernel always match the buffers they describe.
+//! Syscalls which can break memory safety on their own (`thread_create`, mapping memory at a fixed address, ...)
+//! are intentionally not wrapped here.
+
+use core::{ptr::NonNull, slice, time::Duration};
+
+use tinyos_abi::{
+    flags::{TaskStateChange, TaskWaitOptions, WaitOptions},
+    types::{FDAction, SysResult},
+};
+
+use crate::{
+    path::Path,
+    syscalls::{self, FileDescriptor, OpenOptions, PageTableFlags},
+};
+
+/// The kernel treats a timeout of 0 as "no timeout" for reads.
+const READ_NO_TIMEOUT: usize = 0;
+/// The kernel treats a negative timeout as "no timeout" for joins and waits.
+const WAIT_NO_TIMEOUT: i64 = -1;
+/// Lets the kernel choose the capacity of a new pipe.
+const PIPE_DEFAULT_CAP: isize = -1;
+
+fn read_timeout(timeout: Option<Duration>) -> usize {
+    timeout
+        .map(|t| (t.as_nanos().div_ceil(1_000_000).min(usize::MAX as u128) as usize).max(1))
+        .unwrap_or(READ_NO_TIMEOUT)
+}
+
+fn wait_timeout(timeout: Option<Duration>) -> i64 {
+    timeout
+        .map(|t| t.as_millis().min(i64::MAX as u128) as i64)
+        .unwrap_or(WAIT_NO_TIMEOUT)
+}
+
+/// converts a byte count returned by the kernel into a usize, which is at most max
+fn byte_count(n: isize, max: usize) -> usize {
+    debug_assert!(n >= 0 && n as usize <= max);
+    (n.max(0) as usize).min(max)
+}
+
+pub fn exit(status: i64) -> ! {
+    unsafe { syscalls::exit(status) }
+}
+
+pub fn kill(pid: u64, status: i64) -> SysResult<()> {
+    unsafe { syscalls::kill(pid, status) }
+}
+
+pub fn open<P: AsRef<Path> + ?Sized>(path: &P, flags: OpenOptions) -> SysResult<FileDescriptor> {
+    let path = path.as_ref().as_str();
+    unsafe { syscalls::open(path.as_ptr(), path.len(), flags) }
+}
+
+pub fn close(fd: FileDescriptor) -> SysResult<()> {
+    unsafe { syscalls::close(fd) }
+}
+
+/// sets the absolute offset of fd
+pub fn seek(fd: FileDescriptor, offset: usize) -> SysResult<()> {
+    unsafe { syscalls::seek(fd, offset) }
+}
+
+/// duplicates old into the lowest free descriptor, or into new if it is Some
+pub fn dup(old: FileDescriptor, new: Option<FileDescriptor>) -> SysResult<FileDescriptor> {
+    unsafe { syscalls::dup(old, new) }
+}
+
+/// writes buf to fd and returns the number of bytes written
+pub fn write(fd: FileDescriptor, buf: &[u8]) -> SysResult<usize> {
+    unsafe { syscalls::write(fd, buf.as_ptr(), buf.len()) }.map(|n| byte_count(n, buf.len()))
+}
+
+/// reads from fd into buf and returns the number of bytes read.
+/// if timeout is None, the kernel may block indefinitely.
+pub fn read(fd: FileDescriptor, buf: &mut [u8], timeout: Option<Duration>) -> SysResult<usize> {
+    unsafe { syscalls::read(fd, buf.as_mut_ptr(), buf.len(), read_timeout(timeout)) }
+        .map(|n| byte_count(n, buf.len()))
+}
+
+pub fn yield_now() {
+    unsafe { syscalls::yield_now() }
+}
+
+/// A memory mapping created by [`mmap`], which is unmapped on drop.
+#[derive(Debug)]
+pub struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+impl Mapping {
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Leaks the mapping, keeping the memory mapped for the rest of the process.
+    pub fn leak(self) -> &'static mut [u8] {
+        let this = core::mem::ManuallyDrop::new(self);
+        unsafe { slice::from_raw_parts_mut(this.ptr.as_ptr(), this.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { syscalls::munmap(self.ptr.as_ptr(), self.len) };
+    }
+}
+
+/// maps len bytes at an address chosen by the kernel.
+/// if fd is Some, the mapping is backed by fd (starting at its current offset).
+pub fn mmap(len: usize, flags: PageTableFlags, fd: Option<FileDescriptor>) -> SysResult<Mapping> {
+    let ptr = unsafe { syscalls::mmap(len, core::ptr::null_mut(), flags, fd) }?;
+    // the kernel never hands out the null page
+    let ptr = NonNull::new(ptr).expect("kernel returned a null mapping");
+    Ok(Mapping { ptr, len })
+}
+
+/// returns true in the parent, false in the child
+pub fn fork() -> SysResult<bool> {
+    unsafe { syscalls::fork() }
+}
+
+pub fn get_pid() -> SysResult<u64> {
+    unsafe { syscalls::get_pid() }
+}
+
+pub fn get_tid() -> u64 {
+    unsafe { syscalls::get_tid() }
+}
+
+pub fn get_pgrid() -> u64 {
+    unsafe { syscalls::get_pgrid() }
+}
+
+/// spawns a new process from an in-memory elf
+pub fn spawn(elf: &[u8]) -> SysResult<()> {
+    unsafe { syscalls::spawn(elf.as_ptr(), elf.len()) }
+}
+
+/// writes msg to the kernels debug (serial) output
+pub fn dbg(msg: &str) -> SysResult<()> {
+    unsafe { syscalls::dbg(msg.as_ptr(), msg.len()) }
+}
+
+/// replaces the current process with the program at path.
+/// args is a ' ' separated list, env a '\0' separated list of key=value pairs, as in [`crate::os`]
+pub fn execve<P: AsRef<Path> + ?Sized>(path: &P, args: &str, env: &str) -> SysResult<u64> {
+    let path = path.as_ref().as_str();
+    unsafe {
+        syscalls::execve(
+            path.as_ptr(),
+            path.len(),
+            args.len(),
+            args.as_ptr(),
+            env.len(),
+            env.as_ptr(),
+        )
+    }
+}
+
+/// spawns the program at path as a new process and returns its pid.
+/// args and env are formatted as in [`execve`], fd_actions are applied to the childs descriptor table
+pub fn spawn_process<P: AsRef<Path> + ?Sized>(
+    path: &P,
+    args: &str,
+    env: &str,
+    fd_actions: &[FDAction],
+) -> SysResult<u64> {
+    let path = path.as_ref().as_str();
+    unsafe {
+        syscalls::spawn_process(
+            path.as_ptr(),
+            path.len(),
+            args.len(),
+            args.as_ptr(),
+            env.len(),
+            env.as_ptr(),
+            fd_actions.as_ptr(),
+            fd_actions.len(),
+        )
+    }
+}
+
+pub fn thread_cancel(tid: u64) -> SysResult<i64> {
+    unsafe { syscalls::thread_cancel(tid) }
+}
+
+/// waits for a state change of thread tid. if timeout is None, waits indefinitely.
+pub fn thread_join(
+    tid: u64,
+    timeout: Option<Duration>,
+    w_flags: WaitOptions,
+    tw_flags: TaskWaitOptions,
+) -> SysResult<TaskStateChange> {
+    unsafe { syscalls::thread_join(tid, wait_timeout(timeout), w_flags, tw_flags) }
+}
+
+/// waits for a state change of process pid. if timeout is None, waits indefinitely.
+pub fn wait_pid(
+    pid: u64,
+    timeout: Option<Duration>,
+    w_flags: WaitOptions,
+    tw_flags: TaskWaitOptions,
+) -> SysResult<TaskStateChange> {
+    unsafe { syscalls::wait_pid(pid, wait_timeout(timeout), w_flags, tw_flags) }
+}
+
+/// Creates an eventfd, a counter in the kernel starting at 0.
+/// Writing a native endian u64 adds it to the counter. Reading 8 bytes blocks until the counter is non-zero,
+/// then returns it and resets it to 0. See [`crate::sync::EventFd`] for an owning wrapper.
+pub fn eventfd() -> SysResult<FileDescriptor> {
+    unsafe { syscalls::eventfd() }.map(|fd| fd as FileDescriptor)
+}
+
+/// blocks the current thread for at least dur
+pub fn waittime(dur: Duration) -> SysResult<()> {
+    unsafe { syscalls::waittime(dur.as_millis() as u64) }
+}
+
+/// returns the time since boot
+pub fn time() -> SysResult<Duration> {
+    unsafe { syscalls::time() }.map(Duration::from_millis)
+}
+
+/// creates a new pipe and returns its [read, write] ends.
+/// if cap is None, the kernel chooses the capacity.
+pub fn pipe(cap: Option<usize>) -> SysResult<[FileDescriptor; 2]> {
+    let mut fds = [0; 2];
+    let cap = cap
+        .map(|cap| isize::try_from(cap).unwrap_or(isize::MAX))
+        .unwrap_or(PIPE_DEFAULT_CAP);
+    unsafe { syscalls::pipe(&mut fds, cap) }?;
+    Ok(fds)
+}
@@ -0,0 +1,292 @@
+use core::fmt::Write;
+
+use tinyos_abi::{
+    flags::{TaskStateChange, TaskWaitOptions, WaitOptions},
+    types::{FDAction, SysResult},
+};
+
+use super::{traced, traced_noreturn, write_bytes_arg, write_flags, write_str_arg};
+use crate::syscalls::{FileDescriptor, OpenOptions, PageTableFlags, backend};
+
+pub unsafe fn exit(status: i64) -> ! {
+    traced_noreturn("Exit", |w| write!(w, "status={}", status));
+    unsafe { backend::exit(status) }
+}
+
+pub unsafe fn kill(pid: u64, status: i64) -> SysResult<()> {
+    traced(
+        "Kill",
+        |w| write!(w, "pid={}, status={}", pid, status),
+        || unsafe { backend::kill(pid, status) },
+    )
+}
+
+pub unsafe fn open(path: *const u8, len: usize, flags: OpenOptions) -> SysResult<FileDescriptor> {
+    traced(
+        "Open",
+        |w| {
+            w.write_str("path=")?;
+            unsafe { write_str_arg(w, path, len) }?;
+            w.write_str(", flags=")?;
+            write_flags(w, &flags)
+        },
+        || unsafe { backend::open(path, len, flags) },
+    )
+}
+
+pub unsafe fn close(fd: FileDescriptor) -> SysResult<()> {
+    traced(
+        "Close",
+        |w| write!(w, "fd={}", fd),
+        || unsafe { backend::close(fd) },
+    )
+}
+
+pub unsafe fn seek(fd: FileDescriptor, offset: usize) -> SysResult<()> {
+    traced(
+        "Seek",
+        |w| write!(w, "fd={}, offset={}", fd, offset),
+        || unsafe { backend::seek(fd, offset) },
+    )
+}
+
+pub unsafe fn dup(old: FileDescriptor, new: Option<FileDescriptor>) -> SysResult<FileDescriptor> {
+    traced(
+        "Dup",
+        |w| write!(w, "old={}, new={:?}", old, new),
+        || unsafe { backend::dup(old, new) },
+    )
+}
+
+pub unsafe fn write(fd: FileDescriptor, buf: *const u8, len: usize) -> SysResult<isize> {
+    traced(
+        "Write",
+        |w| {
+            write!(w, "fd={}, buf=", fd)?;
+            unsafe { write_bytes_arg(w, buf, len) }?;
+            write!(w, ", len={}", len)
+        },
+        || unsafe { backend::write(fd, buf, len) },
+    )
+}
+
+pub unsafe fn read(
+    fd: FileDescriptor,
+    buf: *mut u8,
+    len: usize,
+    timeout: usize,
+) -> SysResult<isize> {
+    traced(
+        "Read",
+        |w| {
+            write!(
+                w,
+                "fd={}, buf={:?}, len={}, timeout={}",
+                fd, buf, len, timeout
+            )
+        },
+        || unsafe { backend::read(fd, buf, len, timeout) },
+    )
+}
+
+pub unsafe fn yield_now() {
+    traced("Yield", |_| Ok(()), || unsafe { backend::yield_now() })
+}
+
+pub unsafe fn mmap(
+    len: usize,
+    ptr: *mut u8,
+    flags: PageTableFlags,
+    fd: Option<FileDescriptor>,
+) -> SysResult<*mut u8> {
+    traced(
+        "Mmap",
+        |w| {
+            write!(w, "len={}, ptr={:?}, flags=", len, ptr)?;
+            write_flags(w, &flags)?;
+            write!(w, ", fd={:?}", fd)
+        },
+        || unsafe { backend::mmap(len, ptr, flags, fd) },
+    )
+}
+
+pub unsafe fn munmap(ptr: *mut u8, len: usize) {
+    traced(
+        "Munmap",
+        |w| write!(w, "ptr={:?}, len={}", ptr, len),
+        || unsafe { backend::munmap(ptr, len) },
+    )
+}
+
+pub unsafe fn fork() -> SysResult<bool> {
+    traced("Fork", |_| Ok(()), || unsafe { backend::fork() })
+}
+
+pub unsafe fn get_pid() -> SysResult<u64> {
+    traced("GetPID", |_| Ok(()), || unsafe { backend::get_pid() })
+}
+
+pub unsafe fn spawn(elf: *const u8, len: usize) -> SysResult<()> {
+    traced(
+        "Spawn",
+        |w| write!(w, "elf={:?}, len={}", elf, len),
+        || unsafe { backend::spawn(elf, len) },
+    )
+}
+
+pub unsafe fn dbg(buf: *const u8, len: usize) -> SysResult<()> {
+    traced(
+        "Dbg",
+        |w| {
+            w.write_str("buf=")?;
+            unsafe { write_bytes_arg(w, buf, len) }?;
+            write!(w, ", len={}", len)
+        },
+        || unsafe { backend::dbg(buf, len) },
+    )
+}
+
+pub unsafe fn execve(
+    path: *const u8,
+    len: usize,
+    argc: usize,
+    argv: *const u8,
+    envc: usize,
+    envp: *const u8,
+) -> SysResult<u64> {
+    traced(
+        "Execve",
+        |w| {
+            w.write_str("path=")?;
+            unsafe { write_str_arg(w, path, len) }?;
+            w.write_str(", args=")?;
+            unsafe { write_str_arg(w, argv, argc) }?;
+            write!(w, ", envc={}", envc)
+        },
+        || unsafe { backend::execve(path, len, argc, argv, envc, envp) },
+    )
+}
+
+pub unsafe fn thread_create(start_routine: *const (), args: *const ()) -> SysResult<u64> {
+    traced(
+        "ThreadCreate",
+        |w| write!(w, "start_routine={:?}, args={:?}", start_routine, args),
+        || unsafe { backend::thread_create(start_routine, args) },
+    )
+}
+
+pub unsafe fn thread_exit() -> ! {
+    traced_noreturn("ThreadExit", |_| Ok(()));
+    unsafe { backend::thread_exit() }
+}
+
+pub unsafe fn thread_cancel(tid: u64) -> SysResult<i64> {
+    traced(
+        "ThreadCancel",
+        |w| write!(w, "tid={}", tid),
+        || unsafe { backend::thread_cancel(tid) },
+    )
+}
+
+pub unsafe fn thread_join(
+    tid: u64,
+    timeout: i64,
+    w_flags: WaitOptions,
+    tw_flags: TaskWaitOptions,
+) -> SysResult<TaskStateChange> {
+    traced(
+        "ThreadJoin",
+        |w| {
+            write!(w, "tid={}, timeout={}, w_flags=", tid, timeout)?;
+            write_flags(w, &w_flags)?;
+            w.write_str(", tw_flags=")?;
+            write_flags(w, &tw_flags)
+        },
+        || unsafe { backend::thread_join(tid, timeout, w_flags, tw_flags) },
+    )
+}
+
+pub unsafe fn wait_pid(
+    pid: u64,
+    timeout: i64,
+    w_flags: WaitOptions,
+    tw_flags: TaskWaitOptions,
+) -> SysResult<TaskStateChange> {
+    traced(
+        "WaitPID",
+        |w| {
+            write!(w, "pid={}, timeout={}, w_flags=", pid, timeout)?;
+            write_flags(w, &w_flags)?;
+            w.write_str(", tw_flags=")?;
+            write_flags(w, &tw_flags)
+        },
+        || unsafe { backend::wait_pid(pid, timeout, w_flags, tw_flags) },
+    )
+}
+
+pub unsafe fn eventfd() -> SysResult<u64> {
+    traced("EventFD", |_| Ok(()), || unsafe { backend::eventfd() })
+}
+
+pub unsafe fn waittime(time: u64) -> SysResult<()> {
+    traced(
+        "WaitTime",
+        |w| write!(w, "time={}", time),
+        || unsafe { backend::waittime(time) },
+    )
+}
+
+pub unsafe fn time() -> SysResult<u64> {
+    traced("Time", |_| Ok(()), || unsafe { backend::time() })
+}
+
+pub unsafe fn get_tid() -> u64 {
+    traced("GetTID", |_| Ok(()), || unsafe { backend::get_tid() })
+}
+
+pub unsafe fn get_pgrid() -> u64 {
+    traced("GetPgrID", |_| Ok(()), || unsafe { backend::get_pgrid() })
+}
+
+pub unsafe fn pipe(fds: *mut [u32; 2], cap: isize) -> SysResult<()> {
+    traced(
+        "Pipe",
+        |w| write!(w, "fds={:?}, cap={}", fds, cap),
+        || unsafe { backend::pipe(fds, cap) },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn spawn_process(
+    path: *const u8,
+    len: usize,
+    argc: usize,
+    argv: *const u8,
+    envc: usize,
+    envp: *const u8,
+    fd_actions: *const FDAction,
+    fd_action_count: usize,
+) -> SysResult<u64> {
+    traced(
+        "SpawnProcess",
+        |w| {
+            w.write_str("path=")?;
+            unsafe { write_str_arg(w, path, len) }?;
+            w.write_str(", args=")?;
+            unsafe { write_str_arg(w, argv, argc) }?;
+            write!(w, ", envc={}, fd_actions={}", envc, fd_action_count)
+        },
+        || unsafe {
+            backend::spawn_process(
+                path,
+                len,
+                argc,
+                argv,
+                envc,
+                envp,
+                fd_actions,
+                fd_action_count,
+            )
+        },
+    )
+}
@@ -0,0 +1,221 @@
+//! `strace`-style syscall tracing.
+//!
+//! With the `trace-syscalls` feature, every function in [`crate::syscalls`] logs its name, decoded arguments,
+//! result and elapsed time to the kernels debug (serial) channel.
+//! Which syscalls are traced is controlled by the `STRACE` environment variable,
+//! a ',' separated list of syscall names, where `all` selects every syscall, `none` disables tracing
+//! and a leading `-` excludes a syscall, e.g. `STRACE=all,-write,-dbg`.
+//! If `STRACE` is not set, nothing is traced.
+
+use core::{
+    fmt::{self, Write},
+    slice,
+};
+
+use bitflags::Flags;
+use tinyos_abi::{flags::TaskStateChange, types::SysResult};
+
+use super::backend;
+use crate::{internal::rt::try_runtime, utils::StackBuf};
+
+pub(crate) mod funcs;
+
+pub const TRACE_VAR: &str = "STRACE";
+
+const LINE_LEN: usize = 256;
+const MAX_BYTES_SHOWN: usize = 32;
+
+/// returns true if the syscall name is selected by filter, no filter selects nothing
+pub fn is_selected(filter: Option<&str>, name: &str) -> bool {
+    let Some(filter) = filter else {
+        return false;
+    };
+    let mut selected = false;
+    for entry in filter.split(',').map(str::trim) {
+        if entry.eq_ignore_ascii_case("all") {
+            selected = true;
+        } else if entry.eq_ignore_ascii_case("none") {
+            selected = false;
+        } else if let Some(excluded) = entry.strip_prefix('-') {
+            if excluded.eq_ignore_ascii_case(name) {
+                selected = false;
+            }
+        } else if entry.eq_ignore_ascii_case(name) {
+            selected = true;
+        }
+    }
+    selected
+}
+
+fn enabled(name: &str) -> bool {
+    let filter = try_runtime()
+        .and_then(|rt| rt.env())
+        .and_then(|env| env.get(TRACE_VAR));
+    is_selected(filter, name)
+}
+
+fn emit(line: &mut StackBuf<LINE_LEN>) {
+    if line.is_truncated() {
+        // make room for the marker, so the line always ends in a newline
+        let keep = line.as_str().floor_char_boundary(LINE_LEN - 5);
+        let mut short = StackBuf::<LINE_LEN>::new();
+        _ = short.write_str(&line.as_str()[..keep]);
+        _ = short.write_str(" ...\n");
+        *line = short;
+    }
+    let s = line.as_str();
+    _ = unsafe { backend::dbg(s.as_ptr(), s.len()) };
+}
+
+fn start_line(name: &str, args: impl FnOnce(&mut dyn Write) -> fmt::Result) -> StackBuf<LINE_LEN> {
+    let mut line = StackBuf::new();
+    let tid = unsafe { backend::get_tid() };
+    _ = write!(line, "[strace] tid={} {}(", tid, name);
+    _ = args(&mut line);
+    _ = line.write_char(')');
+    line
+}
+
+/// traces a call to the syscall name, which runs call
+pub(crate) fn traced<R: TraceValue>(
+    name: &str,
+    args: impl FnOnce(&mut dyn Write) -> fmt::Result,
+    call: impl FnOnce() -> R,
+) -> R {
+    if !enabled(name) {
+        return call();
+    }
+    let mut line = start_line(name, args);
+    let start = unsafe { backend::time() };
+    let ret = call();
+    let end = unsafe { backend::time() };
+    _ = line.write_str(" = ");
+    _ = ret.trace(&mut line);
+    if let (Ok(start), Ok(end)) = (start, end) {
+        _ = write!(line, " <{}ms>", end.saturating_sub(start));
+    }
+    _ = line.write_char('\n');
+    emit(&mut line);
+    ret
+}
+
+/// traces a call to the syscall name, which does not return
+pub(crate) fn traced_noreturn(name: &str, args: impl FnOnce(&mut dyn Write) -> fmt::Result) {
+    if !enabled(name) {
+        return;
+    }
+    let mut line = start_line(name, args);
+    _ = line.write_str(" = ?\n");
+    emit(&mut line);
+}
+
+/// A syscall return value, which can be written to a trace.
+pub(crate) trait TraceValue {
+    fn trace(&self, w: &mut dyn Write) -> fmt::Result;
+}
+
+macro_rules! trace_value_debug {
+    ($($t:ty),*) => {
+        $(
+            impl TraceValue for $t {
+                fn trace(&self, w: &mut dyn Write) -> fmt::Result {
+                    write!(w, "{:?}", self)
+                }
+            }
+        )*
+    };
+}
+
+trace_value_debug!((), bool, u32, u64, i64, isize, *mut u8);
+
+impl TraceValue for TaskStateChange {
+    fn trace(&self, w: &mut dyn Write) -> fmt::Result {
+        write_flags(w, self)
+    }
+}
+
+impl<T: TraceValue> TraceValue for SysResult<T> {
+    fn trace(&self, w: &mut dyn Write) -> fmt::Result {
+        match self {
+            Ok(v) => {
+                w.write_str("Ok(")?;
+                v.trace(w)?;
+                w.write_char(')')
+            }
+            Err(e) => write!(w, "Err({:?})", e),
+        }
+    }
+}
+
+/// writes the names of all set flags, e.g. READ | WRITE
+pub(crate) fn write_flags<F: Flags>(w: &mut dyn Write, flags: &F) -> fmt::Result
+where
+    F::Bits: bitflags::parser::WriteHex,
+{
+    if flags.is_empty() {
+        return w.write_str("empty");
+    }
+    bitflags::parser::to_writer(flags, w)
+}
+
+/// writes the str at ptr, or its raw bytes if it is not valid utf8
+pub(crate) unsafe fn write_str_arg(w: &mut dyn Write, ptr: *const u8, len: usize) -> fmt::Result {
+    if ptr.is_null() {
+        return w.write_str("NULL");
+    }
+    let bytes = unsafe { slice::from_raw_parts(ptr, len) };
+    match str::from_utf8(bytes) {
+        Ok(s) => write!(w, "{:?}", s),
+        Err(_) => unsafe { write_bytes_arg(w, ptr, len) },
+    }
+}
+
+/// writes the first few bytes at ptr as an escaped string
+pub(crate) unsafe fn write_bytes_arg(w: &mut dyn Write, ptr: *const u8, len: usize) -> fmt::Result {
+    if ptr.is_null() {
+        return w.write_str("NULL");
+    }
+    let bytes = unsafe { slice::from_raw_parts(ptr, len.min(MAX_BYTES_SHOWN)) };
+    w.write_char('"')?;
+    for b in bytes {
+        write!(w, "{}", b.escape_ascii())?;
+    }
+    w.write_char('"')?;
+    if len > MAX_BYTES_SHOWN {
+        w.write_str("...")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter() {
+        assert!(!is_selected(None, "Open"));
+        assert!(is_selected(Some("open,read"), "Open"));
+        assert!(!is_selected(Some("open,read"), "Write"));
+        assert!(is_selected(Some("all,-write"), "Read"));
+        assert!(!is_selected(Some("all,-write"), "Write"));
+        assert!(!is_selected(Some("none"), "Read"));
+        assert!(!is_selected(Some(""), "Read"));
+    }
+
+    #[cfg(feature = "mock-kernel")]
+    #[test]
+    fn traces_to_serial() {
+        use crate::syscalls::{OpenOptions, mock, safe};
+
+        let _capture = mock::capture();
+        mock::boot("", "STRACE=open");
+        _ = safe::open("/ram/strace/missing", OpenOptions::READ);
+        let serial = mock::take_serial();
+        let serial = str::from_utf8(&serial).unwrap();
+        assert!(
+            serial
+                .lines()
+                .any(|l| l.contains("Open(path=\"/ram/strace/missing\", flags=READ) = Err("))
+        );
+    }
+}
@@ -0,0 +1,34 @@
+#!/bin/sh
+# Embeds the function symbols of a linked tinyOS program into its .tinyos_symbols section,
+# so that backtraces printed on panic show function names.
+#
+# usage: scripts/embed-symbols.sh <elf>
+# NM and OBJCOPY may be set to use e.g. llvm-nm and llvm-objcopy.
+set -eu
+
+if [ $# -ne 1 ]; then
+    echo "usage: $0 <elf>" >&2
+    exit 1
+fi
+
+elf="$1"
+nm="${NM:-nm}"
+objcopy="${OBJCOPY:-objcopy}"
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+"$objcopy" --dump-section .tinyos_symbols="$tmp/section" "$elf" "$tmp/unused"
+size=$(wc -c < "$tmp/section")
+
+"$nm" -n -C --defined-only "$elf" | grep -E '^[0-9a-fA-F]+ [TtWw] ' > "$tmp/symbols" || true
+len=$(wc -c < "$tmp/symbols")
+
+# the table is nul terminated, so it must leave at least one byte free
+if [ "$len" -ge "$size" ]; then
+    echo "symbol table of $len bytes does not fit into .tinyos_symbols ($size bytes)" >&2
+    exit 1
+fi
+
+truncate -s "$size" "$tmp/symbols"
+"$objcopy" --update-section .tinyos_symbols="$tmp/symbols" "$elf"
@@ -3,8 +3,10 @@ use core::{ptr::null_mut, str::FromStr};
 use alloc::vec::{self, Vec};
 use embedded_graphics::primitives::Rectangle;
 use libtinyos::{
+    fs,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
     println, syscall,
-    syscalls::{self, OpenOptions, PageTableFlags},
+    syscalls::{self, OpenOptions, PageTableFlags, safe},
 };
 
 use crate::{GraphicsError, internal::framebuffer::FrameBuffer};
@@ -63,16 +65,11 @@ impl RawBitMap {
 
     pub fn new_from_kernel_fb(max_size: usize, offset: usize) -> Self {
         let addr = FRAMEBUFFER_START_ADDR as *mut u8;
-        let fb = unsafe {
-            syscalls::open(
-                KERNEL_FB.as_ptr(),
-                KERNEL_FB.bytes().len(),
-                syscalls::OpenOptions::WRITE,
-            )
-        }
-        .unwrap();
+        // closed once mapped, the mapping stays valid on its own
+        let fb =
+            unsafe { OwnedFd::from_raw_fd(safe::open(KERNEL_FB, OpenOptions::WRITE).unwrap()) };
 
-        unsafe { syscalls::seek(fb, offset) }.unwrap();
+        safe::seek(fb.as_raw_fd(), offset).unwrap();
 
         let addr = unsafe {
             syscalls::mmap(
@@ -81,7 +78,7 @@ impl RawBitMap {
                 PageTableFlags::USER_ACCESSIBLE
                     | PageTableFlags::WRITABLE
                     | PageTableFlags::PRESENT,
-                Some(fb),
+                Some(fb.as_raw_fd()),
             )
         }
         .unwrap();
@@ -119,22 +116,7 @@ pub struct GFXConfig {
 
 impl GFXConfig {
     pub fn new() -> Self {
-        // TODO write abstraction for this in libtinyos::io
-        let f = "/ram/.devconf/gfx/config.conf";
-        let file =
-            unsafe { syscalls::open(f.as_ptr(), f.bytes().len(), OpenOptions::READ) }.unwrap();
-        let mut buffer = Vec::new();
-        let mut idx = 0;
-        buffer.extend_from_slice(&[0; 10]);
-        while let Ok(read) =
-            unsafe { syscalls::read(file, buffer[idx..].as_mut_ptr(), buffer.len() - idx, 0) }
-            && read > 0
-        {
-            idx += read as usize;
-            buffer.extend_from_slice(&[0; 10]);
-        }
-
-        let str_ = str::from_utf8(&buffer[..idx]).unwrap();
+        let str_ = fs::read_to_string("/ram/.devconf/gfx/config.conf").unwrap();
         let mut components = str_.split_whitespace();
 
         fn parse_t_from_str<T: FromStr>(
@@ -4,7 +4,10 @@ use embedded_graphics::{
     prelude::{Dimensions, PixelColor, RgbColor},
     primitives::Rectangle,
 };
-use libtinyos::syscalls::{self, PageTableFlags};
+use libtinyos::{
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    syscalls::{self, OpenOptions, PageTableFlags, safe},
+};
 
 use crate::{
     GraphicsError,
@@ -184,16 +187,11 @@ impl KernelFBWrapper {
 
         let addr = FRAMEBUFFER_START_ADDR as *mut u8;
 
-        let fb = unsafe {
-            syscalls::open(
-                KERNEL_FB.as_ptr(),
-                KERNEL_FB.bytes().len(),
-                syscalls::OpenOptions::WRITE,
-            )
-        }
-        .unwrap();
+        // closed once mapped, the mapping stays valid on its own
+        let fb =
+            unsafe { OwnedFd::from_raw_fd(safe::open(KERNEL_FB, OpenOptions::WRITE).unwrap()) };
 
-        unsafe { syscalls::seek(fb, 0) }.unwrap();
+        safe::seek(fb.as_raw_fd(), 0).unwrap();
 
         let size = (dim.pitch * dim.height) as usize;
 
@@ -204,7 +202,7 @@ impl KernelFBWrapper {
                 PageTableFlags::USER_ACCESSIBLE
                     | PageTableFlags::WRITABLE
                     | PageTableFlags::PRESENT,
-                Some(fb),
+                Some(fb.as_raw_fd()),
             )
         }
         .unwrap();
@@ -284,3 +282,23 @@ impl FrameBuffer for KernelFBWrapper {
 // the underlying memory will remanin valid for the entire lifetime of the program
 unsafe impl Sync for KernelFBWrapper {}
 unsafe impl Send for KernelFBWrapper {}
+
+#[cfg(all(test, feature = "mock-kernel"))]
+mod tests {
+    use embedded_graphics::pixelcolor::Rgb888;
+    use libtinyos::syscalls::mock;
+
+    use super::*;
+
+    #[test]
+    fn kernel_fb_writes_through() {
+        let fb = KernelFBWrapper::new();
+        assert_eq!(fb.width(), mock::FB_WIDTH);
+        assert_eq!(fb.height(), mock::FB_HEIGHT);
+
+        fb.set_pixel(3, 2, &Rgb888::new(0xff, 0, 0x01));
+        let offset = fb.pixel_offset(3, 2) as usize;
+        let pixel = mock::framebuffer()[offset..offset + 4].try_into().unwrap();
+        assert_eq!(u32::from_ne_bytes(pixel), 0x00ff_0001);
+    }
+}